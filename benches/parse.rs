@@ -0,0 +1,53 @@
+// Scan and parse speed on a large generated program, a baseline to catch
+// regressions against. There is no boxed tree left to compare with. Run with
+// `cargo bench --bench parse`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use rlox::ast::Ast;
+use rlox::{Parser, Scanner, Token};
+
+// Some 200k tokens of declarations, loops and nested expressions, the kind
+// of source the parser sees from real scripts.
+fn program() -> String {
+    let mut source = String::new();
+    for i in 0..2000 {
+        source.push_str(&format!(
+            "var a{i} = (1 + {i}) * -2 / (3 - {i}) >= 4 and !false or nil == \"s{i}\";\n\
+             for (var j = 0; j < {i}; j = j + 1) {{\n\
+             \x20   if (j == 2 or a{i}) print [j, j * 2][0]; else a{i} = {{\"k\": j}}[\"k\"];\n\
+             }}\n\
+             while (a{i} != nil) {{ a{i} = len(str(a{i})) > 3; }}\n"
+        ));
+    }
+    source
+}
+
+fn tokens(source: &str) -> Vec<Token> {
+    Scanner::new(source.to_string()).scan_tokens().to_vec()
+}
+
+fn parse(c: &mut Criterion) {
+    let source = program();
+    let tokens = tokens(&source);
+
+    let mut group = c.benchmark_group("parse");
+    // A parse takes long enough that the default 100 samples would be slow.
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(tokens.len() as u64));
+    group.bench_function("scan", |b| b.iter(|| self::tokens(&source)));
+    group.bench_function("parse", |b| {
+        b.iter_batched(
+            || tokens.clone(),
+            |tokens| {
+                let mut ast = Ast::new();
+                let statements = Parser::new(tokens, &mut ast).parse().unwrap();
+                (ast, statements)
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
@@ -0,0 +1,13 @@
+use std::{env, path::Path};
+
+// The syntax tree modules are generated from the spec on every build, so the
+// node definitions and the compiled code can't drift apart.
+fn main() {
+    let spec = Path::new("src/lox.ast");
+    println!("cargo:rerun-if-changed={}", spec.display());
+
+    let out_dir = env::var_os("OUT_DIR").expect("OUT_DIR is set by cargo");
+    if let Err(error) = create_ast::generate_all(spec, Path::new(&out_dir)) {
+        panic!("Couldn't generate the AST from {}: {error}", spec.display());
+    }
+}
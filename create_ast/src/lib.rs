@@ -0,0 +1,435 @@
+use ::std::{
+    io::{self},
+    path::Path,
+    fs::write,
+};
+use std::fs;
+
+// How a field refers to nodes of some family: directly, maybe, or as a list.
+#[derive(Clone, Copy, PartialEq)]
+enum Wrapper {
+    Plain,
+    Option,
+    Vec,
+}
+
+fn child_family<'a>(type_name: &str, families: &[&'a str]) -> Option<(Wrapper, &'a str)> {
+    let type_name = type_name.trim();
+    let (wrapper, inner) = if let Some(inner) = type_name.strip_prefix("Option<") {
+        (Wrapper::Option, inner.strip_suffix('>')?)
+    } else if let Some(inner) = type_name.strip_prefix("Vec<") {
+        (Wrapper::Vec, inner.strip_suffix('>')?)
+    } else {
+        (Wrapper::Plain, type_name)
+    };
+
+    families
+        .iter()
+        .find(|family| **family == inner.trim())
+        .map(|family| (wrapper, *family))
+}
+
+fn field_type(type_name: &str, families: &[&str]) -> String {
+    let type_name = type_name.trim();
+    if let Some(inner) = type_name.strip_prefix("Option<").and_then(|t| t.strip_suffix('>')) {
+        return format!("Option<{}>", field_type(inner, families));
+    }
+    if let Some(inner) = type_name.strip_prefix("Vec<").and_then(|t| t.strip_suffix('>')) {
+        return format!("Vec<{}>", field_type(inner, families));
+    }
+
+    match type_name {
+        "Token" => "crate::Token".to_string(),
+        "Literal" => "crate::Value".to_string(),
+        _ if families.contains(&type_name) => format!("{type_name}Id"),
+        _ => type_name.to_string(),
+    }
+}
+
+fn children_definition(base_name: &str, fields: &str, families: &[&str]) -> String {
+    let mut plain = vec![];
+    let mut collected = String::new();
+    let mut wrapped = false;
+
+    for field in fields.split(',') {
+        if let Some((name, type_name)) = field.split_once(':') {
+            let name = name.trim();
+            match child_family(type_name, families) {
+                Some((Wrapper::Plain, family)) if family == base_name => {
+                    plain.push(format!("self.{name}"));
+                    collected.push_str(&format!("        children.push(self.{name});\n"));
+                }
+                Some((Wrapper::Option, family)) if family == base_name => {
+                    wrapped = true;
+                    collected.push_str(&format!("        children.extend(self.{name});\n"));
+                }
+                Some((Wrapper::Vec, family)) if family == base_name => {
+                    wrapped = true;
+                    collected.push_str(&format!("        children.extend(self.{name}.iter().copied());\n"));
+                }
+                _ => (),
+            }
+        }
+    }
+
+    if !wrapped {
+        return format!("vec![{}]", plain.join(", "));
+    }
+    format!("let mut children = vec![];\n{collected}        children")
+}
+
+fn define_type(base_name: &str, struct_name: &str, fields: &str, families: &[&str]) -> String {
+    let fields_binding = fields
+        .split(',')
+        .filter_map(|field| field.split_once(':'))
+        .map(|(name, type_name)| (name.trim(), field_type(type_name, families)))
+        .collect::<Vec<_>>();
+
+    let struct_body = fields_binding
+        .iter()
+        .map(|(name, type_name)| format!("    pub {name}: {type_name},\n"))
+        .collect::<String>();
+
+    let fn_arguments = fields_binding
+        .iter()
+        .map(|(name, type_name)| format!("{name}: {type_name}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let new_arguments = fields_binding
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        r#"pub struct {struct_name} {{
+{struct_body}}}
+
+impl {struct_name} {{
+    pub fn new({fn_arguments}) -> Self {{
+        Self {{ {new_arguments} }}
+    }}
+}}
+
+impl {base_name} for {struct_name} {{
+    fn as_any(&self) -> &dyn Any {{
+        self
+    }}
+    fn children(&self) -> Vec<{base_name}Id> {{
+        {children_return_statement}
+    }}
+}}"#,
+        children_return_statement = children_definition(base_name, fields, families),
+    )
+}
+
+fn imports_definition(base_name: &str, types: &[&str], families: &[&str]) -> String {
+    let mut imports = vec!["Ast".to_string()];
+    for family in families {
+        if *family == base_name || referenced_families(types, families).contains(family) {
+            imports.push(format!("{family}Id"));
+        }
+    }
+
+    format!("use std::any::Any;\n\nuse crate::ast::{{{}}};", imports.join(", "))
+}
+
+// `IndexSet` -> `index_set`, for the method names of each node.
+fn snake_case(type_name: &str) -> String {
+    let mut name = String::new();
+    for (index, char) in type_name.chars().enumerate() {
+        if char.is_uppercase() && index > 0 {
+            name.push('_');
+        }
+        name.extend(char.to_lowercase());
+    }
+    name
+}
+
+fn visitor_trait_definition(base_name: &str, types: &[&str]) -> String {
+    let body = types
+        .iter()
+        .filter_map(|t| {
+            if let Some((type_name, _)) = t.split_once('=') {
+                let type_name = type_name.trim();
+                let fn_name = format!("visit_{}_{}", snake_case(type_name), base_name.to_lowercase());
+                Some(format!(
+                    "    fn {}(&mut self, id: {base_name}Id, {}: &{}) -> R;\n",
+                    fn_name,
+                    base_name.to_lowercase(),
+                    type_name
+                ))
+            } else {
+                None
+            }
+        })
+        .collect::<String>();
+
+    format!("pub trait Visitor<R> {{\n{body}}}")
+}
+
+// Nodes live in the `Ast` as trait objects, so there is no generic `accept`
+// method to call: the node is downcast to find its `visit_*` method, the same
+// way `Fold` dispatches.
+fn accept_definition(base_name: &str, types: &[&str]) -> String {
+    let param = base_name.to_lowercase();
+    let dispatch = types
+        .iter()
+        .filter_map(|t| t.split_once('='))
+        .map(|(name, _)| {
+            let name = name.trim();
+            format!(
+                "    if let Some({param}) = node.as_any().downcast_ref::<{name}>() {{\n        return visitor.visit_{}_{param}(id, {param});\n    }}\n",
+                snake_case(name)
+            )
+        })
+        .collect::<String>();
+
+    format!(
+        "pub fn accept<R>(node: &dyn {base_name}, id: {base_name}Id, visitor: &mut dyn Visitor<R>) -> R {{\n{dispatch}    unreachable!()\n}}"
+    )
+}
+
+// Every family, the generated one included, that its nodes point into.
+fn referenced_families<'a>(types: &[&str], families: &[&'a str]) -> Vec<&'a str> {
+    let mut referenced = vec![];
+    for t in types {
+        if let Some((_, fields)) = t.split_once('=') {
+            for field in fields.split(',') {
+                if let Some((_, type_name)) = field.split_once(':') {
+                    if let Some((_, family)) = child_family(type_name, families) {
+                        if !referenced.contains(&family) {
+                            referenced.push(family);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    referenced
+}
+
+// Default methods rebuild a node only when one of its children was replaced,
+// so a pass that overrides a single method leaves the rest of the tree shared.
+fn fold_method_definition(base_name: &str, struct_name: &str, fields: &str, families: &[&str]) -> String {
+    let param = base_name.to_lowercase();
+    let mut folds = String::new();
+    let mut unchanged = vec![];
+    let mut arguments = vec![];
+
+    for field in fields.split(',') {
+        if let Some((name, type_name)) = field.split_once(':') {
+            let name = name.trim();
+            let fold = match child_family(type_name, families) {
+                Some((Wrapper::Plain, family)) => {
+                    format!("self.fold_{}(ast, {param}.{name})", family.to_lowercase())
+                }
+                Some((Wrapper::Option, family)) => format!(
+                    "{param}.{name}.map(|child| self.fold_{}(ast, child))",
+                    family.to_lowercase()
+                ),
+                Some((Wrapper::Vec, family)) => format!(
+                    "{param}.{name}.iter().map(|child| self.fold_{}(ast, *child)).collect::<Vec<_>>()",
+                    family.to_lowercase()
+                ),
+                None => {
+                    arguments.push(format!("{param}.{name}.clone()"));
+                    continue;
+                }
+            };
+            folds.push_str(&format!("        let {name} = {fold};\n"));
+            unchanged.push(format!("{name} == {param}.{name}"));
+            arguments.push(name.to_string());
+        }
+    }
+
+    let fn_name = format!("fold_{}_{param}", snake_case(struct_name));
+    if unchanged.is_empty() {
+        return format!(
+            "    fn {fn_name}(&mut self, _ast: &mut Ast, id: {base_name}Id, _{param}: &{struct_name}) -> {base_name}Id {{\n        id\n    }}\n"
+        );
+    }
+
+    format!(
+        "    fn {fn_name}(&mut self, ast: &mut Ast, id: {base_name}Id, {param}: &{struct_name}) -> {base_name}Id {{\n{folds}        if {} {{\n            return id;\n        }}\n        let span = ast.{param}_span(id);\n        ast.alloc_{param}({struct_name}::new({}), span)\n    }}\n",
+        unchanged.join(" && "),
+        arguments.join(", "),
+    )
+}
+
+fn fold_trait_definition(base_name: &str, types: &[&str], families: &[&str]) -> String {
+    let param = base_name.to_lowercase();
+    let mut dispatch = String::new();
+    let mut methods = String::new();
+
+    for t in types {
+        if let Some((name, fields)) = t.split_once('=') {
+            let name = name.trim();
+            dispatch.push_str(&format!(
+                "        if let Some({param}) = node.as_any().downcast_ref::<{name}>() {{\n            return self.fold_{}_{param}(ast, id, {param});\n        }}\n",
+                snake_case(name)
+            ));
+            methods.push('\n');
+            methods.push_str(&fold_method_definition(base_name, name, fields, families));
+        }
+    }
+
+    let supertraits = referenced_families(types, families)
+        .iter()
+        .filter(|family| **family != base_name)
+        .map(|family| format!("crate::{}::Fold", family.to_lowercase()))
+        .collect::<Vec<_>>();
+    let supertrait = if supertraits.is_empty() {
+        String::new()
+    } else {
+        format!(": {}", supertraits.join(" + "))
+    };
+
+    format!(
+        r#"pub trait Fold{supertrait} {{
+    fn fold_{param}(&mut self, ast: &mut Ast, id: {base_name}Id) -> {base_name}Id {{
+        let node = ast.{param}(id);
+{dispatch}        unreachable!()
+    }}
+{methods}}}"#
+    )
+}
+
+fn define_ast(base_name: &str, types: &[&str], families: &[&str]) -> String {
+    let mut items = vec![
+        imports_definition(base_name, types, families),
+        visitor_trait_definition(base_name, types),
+        accept_definition(base_name, types),
+        fold_trait_definition(base_name, types, families),
+        format!(
+            r#"pub trait {base_name} {{
+    fn children(&self) -> Vec<{base_name}Id>;
+    fn as_any(&self) -> &dyn Any;
+}}"#
+        ),
+    ];
+
+    for t in types {
+        if let Some((name, fields)) = t.split_once('=') {
+            items.push(define_type(base_name, name.trim(), fields.trim(), families));
+        }
+    }
+
+    let mut output = items.join("\n\n");
+    output.push('\n');
+    output
+}
+
+// A spec is a list of families, each one a block of `Name = field: Type, ...`
+// lines:
+//
+//     Expr {
+//         Binary = left: Expr, operator: Token, right: Expr
+//     }
+fn parse_spec(spec: &str) -> io::Result<Vec<(&str, Vec<&str>)>> {
+    let invalid = |line: usize, message: &str| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("[{line}] {message}"))
+    };
+
+    let mut families: Vec<(&str, Vec<&str>)> = vec![];
+    let mut open = false;
+
+    for (number, line) in spec.lines().enumerate() {
+        let number = number + 1;
+        let line = line.split("//").next().unwrap_or_default().trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = line.strip_suffix('{') {
+            if open {
+                return Err(invalid(number, "Families can't be nested."));
+            }
+            families.push((name.trim(), vec![]));
+            open = true;
+        } else if line == "}" {
+            if !open {
+                return Err(invalid(number, "Unexpected '}'."));
+            }
+            open = false;
+        } else if open && line.contains('=') {
+            if let Some((_, types)) = families.last_mut() {
+                types.push(line);
+            }
+        } else {
+            return Err(invalid(number, "Expect 'Name = field: Type, ...' inside a family."));
+        }
+    }
+
+    if open {
+        return Err(invalid(spec.lines().count(), "Expect '}' after family."));
+    }
+    Ok(families)
+}
+
+/// Names of the families declared in `spec`, in order.
+pub fn families(spec: &str) -> io::Result<Vec<String>> {
+    Ok(parse_spec(spec)?
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .collect())
+}
+
+/// Generates the Rust module for one family of `spec`.
+pub fn generate(spec: &str, family: &str) -> io::Result<String> {
+    let definitions = parse_spec(spec)?;
+    let families = definitions.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+
+    match definitions.iter().find(|(name, _)| *name == family) {
+        Some((name, types)) => Ok(define_ast(name, types, &families)),
+        None => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No family '{family}' in spec"),
+        )),
+    }
+}
+
+/// Writes every family of the spec at `spec_path` to `<out_dir>/<family>.rs`,
+/// which is what the build script includes.
+pub fn generate_all(spec_path: &Path, out_dir: &Path) -> io::Result<()> {
+    let spec = fs::read_to_string(spec_path)?;
+
+    for family in families(&spec)? {
+        let output = generate(&spec, &family)?;
+        write(out_dir.join(format!("{}.rs", family.to_lowercase())), output)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(spec: &str) -> String {
+        match families(spec) {
+            Err(error) => {
+                assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+                error.to_string()
+            }
+            Ok(families) => panic!("expected an error, got {families:?}"),
+        }
+    }
+
+    #[test]
+    fn malformed_lines_are_reported_with_their_number() {
+        let spec = "Expr {\n    // A comment.\n    Literal = value: Literal\n    Binary left: Expr\n}\n";
+        assert_eq!(error(spec), "[4] Expect 'Name = field: Type, ...' inside a family.");
+        assert_eq!(error("Expr {\n}\n}\n"), "[3] Unexpected '}'.");
+        assert_eq!(error("Expr {\nStmt {\n"), "[2] Families can't be nested.");
+        assert_eq!(error("Expr {\n    Literal = value: Literal\n"), "[2] Expect '}' after family.");
+    }
+
+    #[test]
+    fn families_come_in_order() {
+        let spec = "Expr {\n    Literal = value: Literal\n}\n\nStmt {\n    Print = expression: Expr\n}\n";
+        assert_eq!(families(spec).unwrap(), ["Expr", "Stmt"]);
+        assert_eq!(generate(spec, "Decl").unwrap_err().to_string(), "No family 'Decl' in spec");
+    }
+}
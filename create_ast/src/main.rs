@@ -0,0 +1,215 @@
+use std::{
+    env,
+    fs,
+    io::{self, Write},
+    process,
+};
+
+const CONTEXT: usize = 3;
+
+// Exit codes from sysexits.h, the same rlox uses.
+const EX_USAGE: i32 = 64;
+const EX_DATAERR: i32 = 65;
+const EX_NOINPUT: i32 = 66;
+const EX_IOERR: i32 = 74;
+
+fn generate_ast(spec_path: &str, family: &str, output: Option<&str>) -> io::Result<()> {
+    let spec = fs::read_to_string(spec_path)?;
+    let code = create_ast::generate(&spec, family)?;
+
+    match output {
+        Some(output) => fs::write(output, code),
+        None => io::stdout().write_all(code.as_bytes()),
+    }
+}
+
+// Generates in memory and compares against `path`. Returns whether the file
+// is up to date, printing a unified diff when it isn't.
+fn check_ast(spec_path: &str, family: &str, path: &str) -> io::Result<bool> {
+    let spec = fs::read_to_string(spec_path)?;
+    let code = create_ast::generate(&spec, family)?;
+    let current = match fs::read_to_string(path) {
+        Ok(current) => current,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(error) => return Err(error),
+    };
+
+    let (current, code) = (normalized(&current), normalized(&code));
+    if current == code {
+        return Ok(true);
+    }
+
+    print!("--- {path}\n+++ {path} (generated from {spec_path})\n");
+    print!("{}", unified_diff(&current, &code));
+    Ok(false)
+}
+
+// CRLF line endings or a missing last newline don't make a file out of date,
+// and a line diff couldn't show them anyway.
+fn normalized(text: &str) -> String {
+    let mut text = text.replace("\r\n", "\n");
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+    text
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Edit {
+    Keep,
+    Delete,
+    Insert,
+}
+
+// Line diff over the longest common subsequence, good enough for files the
+// size of a generated module.
+fn edits(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut edits = vec![];
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            edits.push(Edit::Keep);
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            edits.push(Edit::Delete);
+            i += 1;
+        } else {
+            edits.push(Edit::Insert);
+            j += 1;
+        }
+    }
+    edits
+}
+
+fn unified_diff(old: &str, new: &str) -> String {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+    let edits = edits(&old, &new);
+
+    // Group changes whose context overlaps into the same hunk.
+    let mut hunks: Vec<(usize, usize)> = vec![];
+    for (index, edit) in edits.iter().enumerate() {
+        if *edit == Edit::Keep {
+            continue;
+        }
+        let start = index.saturating_sub(CONTEXT);
+        let end = (index + CONTEXT + 1).min(edits.len());
+        match hunks.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut result = String::new();
+    let (mut old_line, mut new_line, mut position) = (0, 0, 0);
+    for (start, end) in hunks {
+        for edit in &edits[position..start] {
+            match edit {
+                Edit::Keep => {
+                    old_line += 1;
+                    new_line += 1;
+                }
+                Edit::Delete => old_line += 1,
+                Edit::Insert => new_line += 1,
+            }
+        }
+
+        let hunk = &edits[start..end];
+        let old_count = hunk.iter().filter(|edit| **edit != Edit::Insert).count();
+        let new_count = hunk.iter().filter(|edit| **edit != Edit::Delete).count();
+        result.push_str(&format!(
+            "@@ -{},{old_count} +{},{new_count} @@\n",
+            old_line + usize::from(old_count > 0),
+            new_line + usize::from(new_count > 0),
+        ));
+
+        for edit in hunk {
+            match edit {
+                Edit::Keep => {
+                    result.push_str(&format!(" {}\n", old[old_line]));
+                    old_line += 1;
+                    new_line += 1;
+                }
+                Edit::Delete => {
+                    result.push_str(&format!("-{}\n", old[old_line]));
+                    old_line += 1;
+                }
+                Edit::Insert => {
+                    result.push_str(&format!("+{}\n", new[new_line]));
+                    new_line += 1;
+                }
+            }
+        }
+        position = end;
+    }
+    result
+}
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let result = match &args[..] {
+        [check, spec, family, path] if check == "--check" => match check_ast(spec, family, path) {
+            Ok(true) => Ok(()),
+            Ok(false) => process::exit(1),
+            Err(error) => Err(error),
+        },
+        [spec, family] => generate_ast(spec, family, None),
+        [spec, family, output] => generate_ast(spec, family, Some(output)),
+        _ => {
+            eprintln!("Usage: create_ast <spec.ast> <family> [<output.rs>]");
+            eprintln!("       create_ast --check <spec.ast> <family> <file.rs>");
+            process::exit(EX_USAGE);
+        }
+    };
+
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        let code = match error.kind() {
+            io::ErrorKind::NotFound | io::ErrorKind::PermissionDenied => EX_NOINPUT,
+            // A malformed spec.
+            io::ErrorKind::InvalidData => EX_DATAERR,
+            _ => EX_IOERR,
+        };
+        process::exit(code);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_changed_line_gets_one_hunk_with_context() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\n";
+        let new = "a\nb\nc\nd\nE\nf\ng\nh\n";
+        assert_eq!(
+            unified_diff(old, new),
+            "@@ -2,7 +2,7 @@\n b\n c\n d\n-e\n+E\n f\n g\n h\n"
+        );
+    }
+
+    #[test]
+    fn line_endings_and_the_last_newline_are_not_differences() {
+        assert_eq!(normalized("a\r\nb"), normalized("a\nb\n"));
+        assert_eq!(normalized(""), "");
+        assert_ne!(normalized("a\n\n"), normalized("a\n"));
+    }
+
+    #[test]
+    fn a_new_file_is_all_insertions() {
+        assert_eq!(unified_diff("", "a\nb\n"), "@@ -0,0 +1,2 @@\n+a\n+b\n");
+        assert_eq!(unified_diff("a\n", "a\n"), "");
+    }
+}
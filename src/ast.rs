@@ -0,0 +1,92 @@
+use std::rc::Rc;
+
+use crate::expr::{self, Expr};
+use crate::stmt::{self, Stmt};
+
+/// Stable handle to an expression stored in an [`Ast`]. Passes that need to
+/// attach data to a node (resolution depth, spans, types...) key their side
+/// tables by this ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ExprId(usize);
+
+/// Same as [`ExprId`] for statements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StmtId(usize);
+
+impl ExprId {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl StmtId {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// Region of the source a node was parsed from: `start..end` are offsets
+/// into the source and `line` is where it begins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+}
+
+/// Table of every node built by the `Parser`, indexed by ID. Nodes refer to
+/// their children by ID rather than by pointer, so passes can keep side
+/// tables keyed by node and dropping a tree never recurses.
+///
+/// It is not an arena: each node is an allocation of its own, behind an `Rc`
+/// so a visitor can hold on to the node it is visiting while it keeps
+/// mutating its own state.
+#[derive(Default)]
+pub struct Ast {
+    exprs: Vec<Rc<dyn Expr>>,
+    stmts: Vec<Rc<dyn Stmt>>,
+    expr_spans: Vec<Span>,
+    stmt_spans: Vec<Span>,
+}
+
+impl Ast {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn alloc_expr(&mut self, expr: impl Expr + 'static, span: Span) -> ExprId {
+        self.exprs.push(Rc::new(expr));
+        self.expr_spans.push(span);
+        ExprId(self.exprs.len() - 1)
+    }
+
+    pub fn alloc_stmt(&mut self, stmt: impl Stmt + 'static, span: Span) -> StmtId {
+        self.stmts.push(Rc::new(stmt));
+        self.stmt_spans.push(span);
+        StmtId(self.stmts.len() - 1)
+    }
+
+    pub fn expr(&self, id: ExprId) -> Rc<dyn Expr> {
+        Rc::clone(&self.exprs[id.0])
+    }
+
+    pub fn stmt(&self, id: StmtId) -> Rc<dyn Stmt> {
+        Rc::clone(&self.stmts[id.0])
+    }
+
+    pub fn expr_span(&self, id: ExprId) -> Span {
+        self.expr_spans[id.0]
+    }
+
+    pub fn stmt_span(&self, id: StmtId) -> Span {
+        self.stmt_spans[id.0]
+    }
+
+    pub fn accept_expr<R>(&self, id: ExprId, visitor: &mut dyn expr::Visitor<R>) -> R {
+        expr::accept(self.expr(id).as_ref(), id, visitor)
+    }
+
+    pub fn accept_stmt<R>(&self, id: StmtId, visitor: &mut dyn stmt::Visitor<R>) -> R {
+        stmt::accept(self.stmt(id).as_ref(), id, visitor)
+    }
+}
@@ -0,0 +1,579 @@
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::ast::{Ast, ExprId, StmtId};
+use crate::expr;
+use crate::stmt;
+use crate::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    SExpr,
+    Tree,
+    Json,
+    Dot,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "sexpr" => Ok(Format::SExpr),
+            "tree" => Ok(Format::Tree),
+            "json" => Ok(Format::Json),
+            "dot" => Ok(Format::Dot),
+            _ => Err(format!("Unknown format '{name}', expected sexpr, tree, json or dot")),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum NodeId {
+    Expr(ExprId),
+    Stmt(StmtId),
+}
+
+enum Attr {
+    Text(String),
+    Literal(Value),
+}
+
+enum Child {
+    Expr(ExprId),
+    Stmt(StmtId),
+    MaybeExpr(Option<ExprId>),
+    MaybeStmt(Option<StmtId>),
+    Exprs(Vec<ExprId>),
+    Stmts(Vec<StmtId>),
+}
+
+// What every format needs to know about a node. `head` is the S-expression
+// operator, `kind` names the node everywhere else.
+struct Node {
+    id: NodeId,
+    kind: &'static str,
+    head: String,
+    attrs: Vec<(&'static str, Attr)>,
+    children: Vec<(&'static str, Child)>,
+}
+
+pub struct AstPrinter<'a> {
+    ast: &'a Ast,
+    format: Format,
+}
+
+impl<'a> AstPrinter<'a> {
+    pub fn new(ast: &'a Ast, format: Format) -> Self {
+        AstPrinter { ast, format }
+    }
+
+    pub fn print(&mut self, statements: &[StmtId], out: &mut impl Write) -> io::Result<()> {
+        let printed = statements
+            .iter()
+            .map(|statement| self.stmt(*statement))
+            .collect::<Vec<_>>();
+        self.write(&printed, out)
+    }
+
+    /// Like `print`, for a single expression such as the REPL echoes. In
+    /// JSON it is a single object instead of a list.
+    pub fn print_expr(&mut self, expr: ExprId, out: &mut impl Write) -> io::Result<()> {
+        let printed = self.expr(expr);
+        match self.format {
+            Format::Json => writeln!(out, "{printed}"),
+            _ => self.write(&[printed], out),
+        }
+    }
+
+    fn write(&self, printed: &[String], out: &mut impl Write) -> io::Result<()> {
+        match self.format {
+            Format::SExpr | Format::Tree => {
+                for node in printed {
+                    writeln!(out, "{node}")?;
+                }
+            }
+            Format::Json => writeln!(out, "[{}]", printed.join(", "))?,
+            Format::Dot => {
+                writeln!(out, "digraph ast {{")?;
+                writeln!(out, "  node [shape=box];")?;
+                for line in printed.iter().flat_map(|node| node.lines()) {
+                    writeln!(out, "  {line}")?;
+                }
+                writeln!(out, "}}")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn expr(&mut self, expr: ExprId) -> String {
+        self.ast.accept_expr(expr, self)
+    }
+
+    fn stmt(&mut self, stmt: StmtId) -> String {
+        self.ast.accept_stmt(stmt, self)
+    }
+
+    fn child(&mut self, id: NodeId) -> String {
+        match id {
+            NodeId::Expr(expr) => self.expr(expr),
+            NodeId::Stmt(stmt) => self.stmt(stmt),
+        }
+    }
+
+    fn node(&mut self, node: Node) -> String {
+        match self.format {
+            Format::SExpr => self.sexpr(node),
+            Format::Tree => self.tree(node),
+            Format::Json => self.json(node),
+            Format::Dot => self.dot(node),
+        }
+    }
+
+    // Children in order, with the missing optional ones as `None`.
+    fn flatten(children: Vec<(&'static str, Child)>) -> Vec<(&'static str, Option<NodeId>)> {
+        let mut flat = vec![];
+        for (name, child) in children {
+            match child {
+                Child::Expr(expr) => flat.push((name, Some(NodeId::Expr(expr)))),
+                Child::Stmt(stmt) => flat.push((name, Some(NodeId::Stmt(stmt)))),
+                Child::MaybeExpr(expr) => flat.push((name, expr.map(NodeId::Expr))),
+                Child::MaybeStmt(stmt) => flat.push((name, stmt.map(NodeId::Stmt))),
+                Child::Exprs(exprs) => {
+                    flat.extend(exprs.into_iter().map(|expr| (name, Some(NodeId::Expr(expr)))))
+                }
+                Child::Stmts(stmts) => {
+                    flat.extend(stmts.into_iter().map(|stmt| (name, Some(NodeId::Stmt(stmt)))))
+                }
+            }
+        }
+        flat
+    }
+
+    // (head child child ...), with `nil` standing in for a missing child
+    // unless it is the last one.
+    fn sexpr(&mut self, node: Node) -> String {
+        if node.children.is_empty() {
+            return node.head;
+        }
+
+        let mut children = Self::flatten(node.children);
+        while let Some((_, None)) = children.last() {
+            children.pop();
+        }
+
+        let mut result = format!("({}", node.head);
+        for (_, child) in children {
+            result.push(' ');
+            match child {
+                Some(child) => result.push_str(&self.child(child)),
+                None => result.push_str("nil"),
+            }
+        }
+        result.push(')');
+        result
+    }
+
+    fn tree(&mut self, node: Node) -> String {
+        let mut result = Self::label(&node);
+
+        for (name, child) in Self::flatten(node.children) {
+            if let Some(child) = child {
+                let printed = self.child(child);
+                for (index, line) in printed.lines().enumerate() {
+                    if index == 0 {
+                        result.push_str(&format!("\n  {name}: {line}"));
+                    } else {
+                        result.push_str(&format!("\n  {line}"));
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    fn json(&mut self, node: Node) -> String {
+        let span = match node.id {
+            NodeId::Expr(expr) => self.ast.expr_span(expr),
+            NodeId::Stmt(stmt) => self.ast.stmt_span(stmt),
+        };
+        let id = match node.id {
+            NodeId::Expr(expr) => expr.index(),
+            NodeId::Stmt(stmt) => stmt.index(),
+        };
+
+        let mut fields = vec![
+            format!("\"type\": {}", json_string(node.kind)),
+            format!("\"id\": {id}"),
+            format!(
+                "\"span\": {{\"line\": {}, \"start\": {}, \"end\": {}}}",
+                span.line, span.start, span.end
+            ),
+        ];
+
+        for (name, attr) in &node.attrs {
+            let value = match attr {
+                Attr::Text(text) => json_string(text),
+                Attr::Literal(Value::String(string)) => json_string(string),
+                Attr::Literal(Value::None) => "null".to_string(),
+                Attr::Literal(literal) => literal.to_string(),
+            };
+            fields.push(format!("{}: {value}", json_string(name)));
+        }
+
+        for (name, child) in node.children {
+            let value = match child {
+                Child::Expr(expr) => self.expr(expr),
+                Child::Stmt(stmt) => self.stmt(stmt),
+                Child::MaybeExpr(Some(expr)) => self.expr(expr),
+                Child::MaybeStmt(Some(stmt)) => self.stmt(stmt),
+                Child::MaybeExpr(None) | Child::MaybeStmt(None) => "null".to_string(),
+                Child::Exprs(exprs) => {
+                    let exprs = exprs
+                        .into_iter()
+                        .map(|expr| self.expr(expr))
+                        .collect::<Vec<_>>();
+                    format!("[{}]", exprs.join(", "))
+                }
+                Child::Stmts(stmts) => {
+                    let stmts = stmts
+                        .into_iter()
+                        .map(|stmt| self.stmt(stmt))
+                        .collect::<Vec<_>>();
+                    format!("[{}]", stmts.join(", "))
+                }
+            };
+            fields.push(format!("{}: {value}", json_string(name)));
+        }
+
+        format!("{{{}}}", fields.join(", "))
+    }
+
+    fn dot(&mut self, node: Node) -> String {
+        let name = dot_name(node.id);
+        let mut result = format!("{name} [label={}];", dot_string(&Self::label(&node)));
+
+        for (field, child) in Self::flatten(node.children) {
+            if let Some(child) = child {
+                result.push_str(&format!(
+                    "\n{name} -> {} [label={}];",
+                    dot_name(child),
+                    dot_string(field)
+                ));
+                result.push('\n');
+                result.push_str(&self.child(child));
+            }
+        }
+        result
+    }
+
+    // `Binary +`, `Var x`, `Literal "hi"`...
+    fn label(node: &Node) -> String {
+        let mut label = node.kind.to_string();
+        for (_, attr) in &node.attrs {
+            label.push(' ');
+            match attr {
+                Attr::Text(text) => label.push_str(text),
+                Attr::Literal(literal) => label.push_str(&literal_to_string(literal)),
+            }
+        }
+        label
+    }
+}
+
+fn literal_to_string(literal: &Value) -> String {
+    match literal {
+        Value::None => "nil".to_string(),
+        Value::String(string) => format!("{string:?}"),
+        Value::Number(number) => number.to_string(),
+        Value::Boolean(boolean) => boolean.to_string(),
+        Value::Native(_) | Value::List(_) | Value::Map(_) => literal.to_string(),
+    }
+}
+
+fn json_string(text: &str) -> String {
+    let mut result = String::from('"');
+    for char in text.chars() {
+        match char {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+// DOT accepts the same escapes we need from JSON strings.
+fn dot_string(text: &str) -> String {
+    json_string(text)
+}
+
+fn dot_name(id: NodeId) -> String {
+    match id {
+        NodeId::Expr(expr) => format!("e{}", expr.index()),
+        NodeId::Stmt(stmt) => format!("s{}", stmt.index()),
+    }
+}
+
+impl expr::Visitor<String> for AstPrinter<'_> {
+    fn visit_assign_expr(&mut self, id: ExprId, expr: &expr::Assign) -> String {
+        self.node(Node {
+            id: NodeId::Expr(id),
+            kind: "Assign",
+            head: format!("= {}", expr.name.lexeme),
+            attrs: vec![("name", Attr::Text(expr.name.lexeme.clone()))],
+            children: vec![("value", Child::Expr(expr.value))],
+        })
+    }
+    fn visit_binary_expr(&mut self, id: ExprId, expr: &expr::Binary) -> String {
+        self.node(Node {
+            id: NodeId::Expr(id),
+            kind: "Binary",
+            head: expr.operator.lexeme.clone(),
+            attrs: vec![("operator", Attr::Text(expr.operator.lexeme.clone()))],
+            children: vec![
+                ("left", Child::Expr(expr.left)),
+                ("right", Child::Expr(expr.right)),
+            ],
+        })
+    }
+    fn visit_call_expr(&mut self, id: ExprId, expr: &expr::Call) -> String {
+        self.node(Node {
+            id: NodeId::Expr(id),
+            kind: "Call",
+            head: "call".to_string(),
+            attrs: vec![],
+            children: vec![
+                ("callee", Child::Expr(expr.callee)),
+                ("arguments", Child::Exprs(expr.arguments.clone())),
+            ],
+        })
+    }
+    fn visit_get_expr(&mut self, id: ExprId, expr: &expr::Get) -> String {
+        self.node(Node {
+            id: NodeId::Expr(id),
+            kind: "Get",
+            head: format!(". {}", expr.name.lexeme),
+            attrs: vec![("name", Attr::Text(expr.name.lexeme.clone()))],
+            children: vec![("object", Child::Expr(expr.object))],
+        })
+    }
+    fn visit_grouping_expr(&mut self, id: ExprId, expr: &expr::Grouping) -> String {
+        self.node(Node {
+            id: NodeId::Expr(id),
+            kind: "Grouping",
+            head: "group".to_string(),
+            attrs: vec![],
+            children: vec![("expression", Child::Expr(expr.expression))],
+        })
+    }
+    fn visit_index_expr(&mut self, id: ExprId, expr: &expr::Index) -> String {
+        self.node(Node {
+            id: NodeId::Expr(id),
+            kind: "Index",
+            head: "index".to_string(),
+            attrs: vec![],
+            children: vec![
+                ("object", Child::Expr(expr.object)),
+                ("index", Child::Expr(expr.index)),
+            ],
+        })
+    }
+    fn visit_index_set_expr(&mut self, id: ExprId, expr: &expr::IndexSet) -> String {
+        self.node(Node {
+            id: NodeId::Expr(id),
+            kind: "IndexSet",
+            head: "index=".to_string(),
+            attrs: vec![],
+            children: vec![
+                ("object", Child::Expr(expr.object)),
+                ("index", Child::Expr(expr.index)),
+                ("value", Child::Expr(expr.value)),
+            ],
+        })
+    }
+    fn visit_list_expr(&mut self, id: ExprId, expr: &expr::List) -> String {
+        self.node(Node {
+            id: NodeId::Expr(id),
+            kind: "List",
+            head: "list".to_string(),
+            attrs: vec![],
+            children: vec![("elements", Child::Exprs(expr.elements.clone()))],
+        })
+    }
+    fn visit_literal_expr(&mut self, id: ExprId, expr: &expr::Literal) -> String {
+        self.node(Node {
+            id: NodeId::Expr(id),
+            kind: "Literal",
+            head: literal_to_string(&expr.value),
+            attrs: vec![("value", Attr::Literal(expr.value.clone()))],
+            children: vec![],
+        })
+    }
+    fn visit_logical_expr(&mut self, id: ExprId, expr: &expr::Logical) -> String {
+        self.node(Node {
+            id: NodeId::Expr(id),
+            kind: "Logical",
+            head: expr.operator.lexeme.clone(),
+            attrs: vec![("operator", Attr::Text(expr.operator.lexeme.clone()))],
+            children: vec![
+                ("left", Child::Expr(expr.left)),
+                ("right", Child::Expr(expr.right)),
+            ],
+        })
+    }
+    // An S-expression pairs each key with its value, `(map (k1 v1) (k2 v2))`,
+    // or it couldn't be read back. The other formats name the two lists.
+    fn visit_map_expr(&mut self, id: ExprId, expr: &expr::Map) -> String {
+        if self.format == Format::SExpr {
+            let mut result = "(map".to_string();
+            for (key, value) in expr.keys.iter().zip(&expr.values) {
+                result.push_str(&format!(" ({} {})", self.expr(*key), self.expr(*value)));
+            }
+            result.push(')');
+            return result;
+        }
+        self.node(Node {
+            id: NodeId::Expr(id),
+            kind: "Map",
+            head: "map".to_string(),
+            attrs: vec![],
+            children: vec![
+                ("keys", Child::Exprs(expr.keys.clone())),
+                ("values", Child::Exprs(expr.values.clone())),
+            ],
+        })
+    }
+    fn visit_unary_expr(&mut self, id: ExprId, expr: &expr::Unary) -> String {
+        self.node(Node {
+            id: NodeId::Expr(id),
+            kind: "Unary",
+            head: expr.operator.lexeme.clone(),
+            attrs: vec![("operator", Attr::Text(expr.operator.lexeme.clone()))],
+            children: vec![("right", Child::Expr(expr.right))],
+        })
+    }
+    fn visit_variable_expr(&mut self, id: ExprId, expr: &expr::Variable) -> String {
+        self.node(Node {
+            id: NodeId::Expr(id),
+            kind: "Variable",
+            head: expr.name.lexeme.clone(),
+            attrs: vec![("name", Attr::Text(expr.name.lexeme.clone()))],
+            children: vec![],
+        })
+    }
+}
+
+impl stmt::Visitor<String> for AstPrinter<'_> {
+    fn visit_block_stmt(&mut self, id: StmtId, stmt: &stmt::Block) -> String {
+        self.node(Node {
+            id: NodeId::Stmt(id),
+            kind: "Block",
+            head: "block".to_string(),
+            attrs: vec![],
+            children: vec![("statements", Child::Stmts(stmt.statements.clone()))],
+        })
+    }
+    fn visit_break_stmt(&mut self, id: StmtId, _stmt: &stmt::Break) -> String {
+        self.node(Node {
+            id: NodeId::Stmt(id),
+            kind: "Break",
+            head: "break".to_string(),
+            attrs: vec![],
+            children: vec![],
+        })
+    }
+    fn visit_continue_stmt(&mut self, id: StmtId, _stmt: &stmt::Continue) -> String {
+        self.node(Node {
+            id: NodeId::Stmt(id),
+            kind: "Continue",
+            head: "continue".to_string(),
+            attrs: vec![],
+            children: vec![],
+        })
+    }
+    fn visit_expression_stmt(&mut self, id: StmtId, stmt: &stmt::Expression) -> String {
+        self.node(Node {
+            id: NodeId::Stmt(id),
+            kind: "Expression",
+            head: ";".to_string(),
+            attrs: vec![],
+            children: vec![("expression", Child::Expr(stmt.expression))],
+        })
+    }
+    fn visit_for_stmt(&mut self, id: StmtId, stmt: &stmt::For) -> String {
+        self.node(Node {
+            id: NodeId::Stmt(id),
+            kind: "For",
+            head: "for".to_string(),
+            attrs: vec![],
+            children: vec![
+                ("initializer", Child::MaybeStmt(stmt.initializer)),
+                ("condition", Child::MaybeExpr(stmt.condition)),
+                ("increment", Child::MaybeExpr(stmt.increment)),
+                ("body", Child::Stmt(stmt.body)),
+            ],
+        })
+    }
+    fn visit_for_in_stmt(&mut self, id: StmtId, stmt: &stmt::ForIn) -> String {
+        self.node(Node {
+            id: NodeId::Stmt(id),
+            kind: "ForIn",
+            head: format!("for {} in", stmt.name.lexeme),
+            attrs: vec![("name", Attr::Text(stmt.name.lexeme.clone()))],
+            children: vec![
+                ("iterable", Child::Expr(stmt.iterable)),
+                ("body", Child::Stmt(stmt.body)),
+            ],
+        })
+    }
+    fn visit_if_stmt(&mut self, id: StmtId, stmt: &stmt::If) -> String {
+        self.node(Node {
+            id: NodeId::Stmt(id),
+            kind: "If",
+            head: "if".to_string(),
+            attrs: vec![],
+            children: vec![
+                ("condition", Child::Expr(stmt.condition)),
+                ("then_branch", Child::Stmt(stmt.then_branch)),
+                ("else_branch", Child::MaybeStmt(stmt.else_branch)),
+            ],
+        })
+    }
+    fn visit_print_stmt(&mut self, id: StmtId, stmt: &stmt::Print) -> String {
+        self.node(Node {
+            id: NodeId::Stmt(id),
+            kind: "Print",
+            head: "print".to_string(),
+            attrs: vec![],
+            children: vec![("expression", Child::Expr(stmt.expression))],
+        })
+    }
+    fn visit_var_stmt(&mut self, id: StmtId, stmt: &stmt::Var) -> String {
+        self.node(Node {
+            id: NodeId::Stmt(id),
+            kind: "Var",
+            head: format!("var {}", stmt.name.lexeme),
+            attrs: vec![("name", Attr::Text(stmt.name.lexeme.clone()))],
+            children: vec![("initializer", Child::MaybeExpr(stmt.initializer))],
+        })
+    }
+    fn visit_while_stmt(&mut self, id: StmtId, stmt: &stmt::While) -> String {
+        self.node(Node {
+            id: NodeId::Stmt(id),
+            kind: "While",
+            head: "while".to_string(),
+            attrs: vec![],
+            children: vec![
+                ("condition", Child::Expr(stmt.condition)),
+                ("body", Child::Stmt(stmt.body)),
+                ("increment", Child::MaybeExpr(stmt.increment)),
+            ],
+        })
+    }
+}
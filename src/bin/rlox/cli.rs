@@ -0,0 +1,173 @@
+use std::fs;
+use std::io::{self, Read};
+
+use rlox::ast_printer::Format;
+
+pub const HELP: &str = "\
+Usage: rlox [command] [options]
+
+Commands:
+  run [--allow-io] <script> [args...]
+                                     runs a script; the same as
+                                     `rlox <script> [args...]`
+  run [--allow-io] -e <code> [args...]
+                                     runs the code given, also as
+                                     `rlox -e <code> [args...]`
+  tokens <script>                    shows the tokens of the script
+  parse [--format=FORMAT] <script>   shows the syntax tree as sexpr (the
+                                     default), tree, json or dot
+  check <script>                     looks for static errors without running
+  fmt [--check] <file>...            formats the files; with --check only
+                                     lists the ones that would change
+  repl [--allow-io] [--format=FORMAT]
+                                     interactive session; the same as no
+                                     arguments
+
+Options:
+  -h, --help     shows this help
+  -V, --version  shows the version
+  --allow-io     lets scripts use readFile, writeFile, exists, listDir,
+                 getenv and args; goes before the script, or before -e
+
+Where a <script> is expected, `-` reads it from standard input.
+
+Exit codes: 64 wrong usage, 65 static errors, 66 unreadable script,
+70 runtime error.";
+
+/// Where a program comes from.
+pub enum Input {
+    File(String),
+    Stdin,
+    /// Given with `-e`.
+    Code(String),
+}
+
+impl Input {
+    fn script(arg: String) -> Self {
+        match arg.as_str() {
+            "-" => Input::Stdin,
+            _ => Input::File(arg),
+        }
+    }
+
+    pub fn read(&self) -> io::Result<String> {
+        match self {
+            Input::File(path) => fs::read_to_string(path)
+                .map_err(|error| io::Error::new(error.kind(), format!("{path}: {error}"))),
+            Input::Stdin => {
+                let mut content = String::new();
+                io::stdin().read_to_string(&mut content)?;
+                Ok(content)
+            }
+            Input::Code(code) => Ok(code.clone()),
+        }
+    }
+}
+
+pub enum Command {
+    /// Arguments after the script are for the script itself.
+    Run { input: Input, args: Vec<String>, allow_io: bool },
+    Tokens { input: Input },
+    Parse { input: Input, format: Format },
+    Check { input: Input },
+    Fmt { check: bool, files: Vec<String> },
+    /// With a format, each input is parsed and its tree printed instead of
+    /// running it.
+    Repl { format: Option<Format>, allow_io: bool },
+    Help,
+    Version,
+}
+
+/// Parses the command line, without the program name.
+pub fn parse_args(args: Vec<String>) -> Result<Command, String> {
+    let (allow_io, args) = take_allow_io(args);
+    let mut args = args.into_iter();
+    let Some(first) = args.next() else {
+        return Ok(Command::Repl { format: None, allow_io });
+    };
+    let rest = args.collect::<Vec<_>>();
+
+    match first.as_str() {
+        "-h" | "--help" => Ok(Command::Help),
+        "-V" | "--version" => Ok(Command::Version),
+        "run" => {
+            let (allow_run_io, rest) = take_allow_io(rest);
+            parse_run(rest, allow_io || allow_run_io)
+        }
+        "tokens" => Ok(Command::Tokens {
+            input: single_script(rest, "Usage: rlox tokens <script>")?,
+        }),
+        "parse" => {
+            let (format, rest) = take_format(rest)?;
+            Ok(Command::Parse {
+                input: single_script(rest, "Usage: rlox parse [--format=FORMAT] <script>")?,
+                format: format.unwrap_or(Format::SExpr),
+            })
+        }
+        "check" => Ok(Command::Check {
+            input: single_script(rest, "Usage: rlox check <script>")?,
+        }),
+        "fmt" => {
+            let check = rest.iter().any(|arg| arg == "--check");
+            let files = rest.into_iter().filter(|arg| arg != "--check").collect::<Vec<_>>();
+            if files.is_empty() {
+                return Err("Usage: rlox fmt [--check] <file>...".to_string());
+            }
+            Ok(Command::Fmt { check, files })
+        }
+        "repl" => {
+            let (format, rest) = take_format(rest)?;
+            let allow_io = allow_io || rest.iter().any(|arg| arg == "--allow-io");
+            if rest.iter().any(|arg| arg != "--allow-io") {
+                return Err("Usage: rlox repl [--allow-io] [--format=FORMAT]".to_string());
+            }
+            Ok(Command::Repl { format, allow_io })
+        }
+        option if option.starts_with('-') && option != "-" && option != "-e" => {
+            Err(format!("Unknown option '{option}', use --help to see the available ones"))
+        }
+        _ => parse_run([vec![first], rest].concat(), allow_io),
+    }
+}
+
+// `--allow-io` goes before the script; after it, it would be one of the
+// script's arguments.
+fn take_allow_io(args: Vec<String>) -> (bool, Vec<String>) {
+    let flags = args.iter().take_while(|arg| *arg == "--allow-io").count();
+    (flags > 0, args[flags..].to_vec())
+}
+
+// `<script> [args...]`, `- [args...]` or `-e <code> [args...]`.
+fn parse_run(args: Vec<String>, allow_io: bool) -> Result<Command, String> {
+    let mut args = args.into_iter();
+    let input = match args.next().as_deref() {
+        Some("-e") => Input::Code(args.next().ok_or("Usage: rlox -e <code> [args...]")?),
+        Some(script) => Input::script(script.to_string()),
+        None => return Err("Usage: rlox run <script> [args...]".to_string()),
+    };
+    Ok(Command::Run {
+        input,
+        args: args.collect(),
+        allow_io,
+    })
+}
+
+fn single_script(args: Vec<String>, usage: &str) -> Result<Input, String> {
+    match <[String; 1]>::try_from(args) {
+        Ok([script]) => Ok(Input::script(script)),
+        Err(_) => Err(usage.to_string()),
+    }
+}
+
+// Pulls `--format=...` out of `args`, wherever it is.
+fn take_format(args: Vec<String>) -> Result<(Option<Format>, Vec<String>), String> {
+    let mut format = None;
+    let mut rest = vec![];
+    for arg in args {
+        match arg.strip_prefix("--format=") {
+            Some(name) => format = Some(name.parse()?),
+            None => rest.push(arg),
+        }
+    }
+    Ok((format, rest))
+}
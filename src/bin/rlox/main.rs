@@ -1,651 +1,371 @@
-mod expr;
-mod ast_printer;
-use std::{env, string::ParseError};
-use colored::Colorize;
+mod cli;
+mod repl;
+
+use cli::Command;
+use rlox::ast::{Ast, StmtId};
+use rlox::ast_printer::{AstPrinter, Format};
+use rlox::formatter::Formatter;
+use rlox::{Interpreter, Limits, Parser, Scanner, SyntaxError, Token, TokenType, Value};
+use rustyline::error::ReadlineError;
 use std::{
-    collections::hash_map::HashMap,
-    env::Args,
+    env::{self, Args},
     error::Error,
-    fmt::Debug,
     fs,
-    io::{self, Write},
-    usize,
+    io::{self, IsTerminal},
+    sync::atomic::{AtomicBool, Ordering},
+    sync::Arc,
 };
 
+// Exit codes from sysexits.h, the same jlox uses.
+const EX_USAGE: i32 = 64;
+const EX_DATAERR: i32 = 65;
+const EX_NOINPUT: i32 = 66;
+const EX_SOFTWARE: i32 = 70;
+const EX_IOERR: i32 = 74;
+
 fn main() {
     if let Err(error) = start(env::args()) {
         eprintln!("Error: {error}");
+        let code = match error.downcast_ref::<io::Error>().map(io::Error::kind) {
+            Some(io::ErrorKind::NotFound | io::ErrorKind::PermissionDenied) => EX_NOINPUT,
+            Some(_) => EX_IOERR,
+            None => EX_SOFTWARE,
+        };
+        std::process::exit(code);
     }
 }
 
-pub fn start(mut args: Args) -> Result<(), Box<dyn Error>> {
-    args.next();
-    match args.len() {
-        2.. => Err("Uso: rlox <script>".into()),
-        1 => Ok(run_file(
-            args.next().expect("No se ha encontrado el archivo"),
-        )?),
-        _ => Ok(run_prompt()?),
-    }
-}
-
-fn run_file(file_path: String) -> Result<(), Box<dyn Error>> {
-    let mut content = fs::read_to_string(file_path)?;
-    run(&mut content);
-    Ok(())
-}
-
-fn run_prompt() -> Result<(), Box<dyn Error>> {
-    loop {
-        print_prompt();
-        let mut buffer = String::new();
-        io::stdin().read_line(&mut buffer)?;
-        if buffer.is_empty() {
-            break;
+pub fn start(args: Args) -> Result<(), Box<dyn Error>> {
+    let command = match cli::parse_args(args.skip(1).collect()) {
+        Ok(command) => command,
+        Err(usage) => {
+            eprintln!("{usage}");
+            std::process::exit(EX_USAGE);
+        }
+    };
+
+    match command {
+        Command::Run { input, args, allow_io } => {
+            let content = input.read()?;
+            let mut interpreter = interpreter(allow_io);
+            interpreter.set_args(args);
+            exit_on_failure(run(&mut interpreter, &content));
         }
-        run(&mut buffer);
+        Command::Tokens { input } => exit_on_failure(print_tokens(&input.read()?)),
+        Command::Parse { input, format } => exit_on_failure(print_ast(&input.read()?, format)),
+        Command::Check { input } => exit_on_failure(check(&input.read()?)),
+        Command::Fmt { check, files } => format_files(check, &files)?,
+        Command::Repl { format, allow_io } => run_prompt(format, allow_io)?,
+        Command::Help => println!("{}", cli::HELP),
+        Command::Version => println!("rlox {}", env!("CARGO_PKG_VERSION")),
     }
     Ok(())
 }
 
-fn print_prompt() {
-    print!("{}", ">> ".bold().green());
-    io::stdout().flush().unwrap();
-}
-
-fn run(content: &mut str) {
-    let mut scanner = Scanner::new(content.to_string());
-    let tokens = scanner.scan_tokens();
-    let mut parser = Parser::new(tokens.to_vec());
-    let expression = parser.parse();
-
-    ast_printer::AstPrinter::new().print(expression);
-}
-
-fn report_error(line: usize, place: &str, message: &str) {
-    eprintln!("[{line}] | Error {place}: {message}");
-    std::process::exit(64);
+fn interpreter(allow_io: bool) -> Interpreter {
+    let mut interpreter = Interpreter::new();
+    interpreter.set_limits(Limits {
+        allow_io,
+        ..Limits::default()
+    });
+    interpreter
 }
 
-fn error(token: Token, message: &str) {
-    if token.token_type == TokenType::EOF {
-        report_error(token.line, " at end", message)
-    } else {
-        let place = format!(" at '{}'", token.lexeme);
-        report_error(token.line, &place, message)
+// Errors have been reported by the time a failure gets here, all that is
+// left is the exit code.
+fn exit_on_failure(result: Result<(), Failure>) {
+    match result {
+        Ok(()) => {}
+        Err(Failure::Static) => std::process::exit(EX_DATAERR),
+        Err(Failure::Runtime) => std::process::exit(EX_SOFTWARE),
     }
 }
 
-
-// -----------------------------------------------------------------------------------------
-// SCANNER
-// -----------------------------------------------------------------------------------------
-
-#[derive(Debug, Clone, PartialEq)]
-pub enum TokenType {
-    LeftParen,
-    RightParen,
-    LeftBrace,
-    RightBrace,
-    Comma,
-    Dot,
-    Minus,
-    Plus,
-    Semicolon,
-    Slash,
-    Star,
-
-    Bang,
-    BangEqual,
-    Equal,
-    EqualEqual,
-    Greater,
-    GreaterEqual,
-    Less,
-    LessEqual,
-
-    Identifier,
-    String,
-    Number,
-
-    And,
-    Class,
-    Else,
-    False,
-    Fun,
-    For,
-    If,
-    Nil,
-    Or,
-    Print,
-    Return,
-    Super,
-    This,
-    True,
-    Var,
-    While,
-
-    EOF,
-}
-
-impl core::fmt::Display for TokenType {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        let token_str = match self {
-            TokenType::LeftParen => "LeftParen",
-            TokenType::RightParen => "RightParen",
-            TokenType::LeftBrace => "LeftBrace",
-            TokenType::RightBrace => "RightBrace",
-            TokenType::Comma => "Comma",
-            TokenType::Dot => "Dot",
-            TokenType::Minus => "Minus",
-            TokenType::Plus => "Plus",
-            TokenType::Semicolon => "Semicolon",
-            TokenType::Slash => "Slash",
-            TokenType::Star => "Star",
-
-            TokenType::Bang => "Bang",
-            TokenType::BangEqual => "BangEqual",
-            TokenType::Equal => "Equal",
-            TokenType::EqualEqual => "EqualEqual",
-            TokenType::Greater => "Greater",
-            TokenType::GreaterEqual => "GreaterEqual",
-            TokenType::Less => "Less",
-            TokenType::LessEqual => "LessEqual",
-
-            TokenType::Identifier => "Identifier",
-            TokenType::String => "String",
-            TokenType::Number => "Number",
-
-            TokenType::And => "And",
-            TokenType::Class => "Class",
-            TokenType::Else => "Else",
-            TokenType::False => "False",
-            TokenType::Fun => "Fun",
-            TokenType::For => "For",
-            TokenType::If => "If",
-            TokenType::Nil => "Nil",
-            TokenType::Or => "Or",
-            TokenType::Print => "Print",
-            TokenType::Return => "Return",
-            TokenType::Super => "Super",
-            TokenType::This => "This",
-            TokenType::True => "True",
-            TokenType::Var => "Var",
-            TokenType::While => "While",
-
-            TokenType::EOF => "EOF",
+// `rlox fmt [--check] files...`: rewrites each file in canonical form. With
+// `--check` nothing is written; the files that would change are listed and
+// the exit code is 1 if there is any.
+fn format_files(check: bool, files: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut unformatted = false;
+    for file in files {
+        let source = fs::read_to_string(file)?;
+        let Ok(formatted) = format_source(&source) else {
+            eprintln!("Couldn't format {file}");
+            std::process::exit(EX_DATAERR);
         };
-        write!(f, "{}", token_str)
+        if formatted == source {
+            continue;
+        }
+        if check {
+            println!("{file}");
+            unformatted = true;
+        } else {
+            fs::write(file, formatted)?;
+        }
     }
-}
-
-#[derive(Debug, Clone)]
-pub enum Value {
-    String(String),
-    Number(f64),
-    Boolean(bool),
-    None,
-}
 
-impl core::fmt::Display for Value {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        let value_str = match self {
-            Self::String(s) => s.to_string(),
-            Self::Number(n) => n.to_string(),
-            Self::Boolean(b) => b.to_string(),
-            Self::None => "None".to_string(),
-        };
-
-        write!(f, "{}", value_str)
+    if unformatted {
+        std::process::exit(1);
     }
+    Ok(())
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Clone)]
-pub struct Token {
-    pub token_type: TokenType,
-    pub lexeme: String,
-    pub literal: Value,
-    pub line: usize,
+fn format_source(source: &str) -> Result<String, Failure> {
+    let mut scanner = Scanner::with_comments(source.to_string());
+    let (comments, tokens): (Vec<Token>, Vec<Token>) = scanner
+        .scan_tokens()
+        .iter()
+        .cloned()
+        .partition(|token| token.token_type == TokenType::Comment);
+    if !scanner.errors().is_empty() {
+        return Err(report_syntax_errors(scanner.errors()));
+    }
+
+    let mut ast = Ast::new();
+    let statements = Parser::new(tokens, &mut ast)
+        .parse()
+        .map_err(|errors| report_syntax_errors(&errors))?;
+    Ok(Formatter::new(&ast, source, comments).format(&statements))
 }
 
-impl Token {
-    pub fn new(token_type: TokenType, lexeme: String, literal: Value, line: usize) -> Self {
-        Self {
-            token_type,
-            lexeme,
-            literal,
-            line,
-        }
+// One interpreter for the whole session, so what a line defines is still
+// there for the next one. Errors are reported and the session goes on.
+//
+// Lines are collected until they make up complete statements, with a `.. `
+// prompt for the ones that continue. An empty line runs whatever was
+// collected as it is, to get out of a statement that can't be finished.
+//
+// When stdin is not a terminal there is nobody to talk to: whatever is piped
+// in runs as a script, without prompts or colors.
+fn run_prompt(format: Option<Format>, allow_io: bool) -> Result<(), Box<dyn Error>> {
+    if !io::stdin().is_terminal() {
+        colored::control::set_override(false);
+        let content = cli::Input::Stdin.read()?;
+        let result = match format {
+            Some(format) => print_ast(&content, format),
+            None => run(&mut interpreter(allow_io), &content),
+        };
+        exit_on_failure(result);
+        return Ok(());
     }
-}
 
-impl core::fmt::Display for Token {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(
-            f,
-            "Token Type: {}\nLexeme: {}\nLiteral: {}\nLine: {}\n",
-            self.token_type, self.lexeme, self.literal, self.line
-        )
+    let mut editor = repl::editor()?;
+    let history = repl::history_path();
+    if let Some(history) = &history {
+        // There is no history yet the first time.
+        let _ = editor.load_history(history);
     }
-}
 
-fn load_keywords() -> HashMap<String, TokenType> {
-    let mut keywords = HashMap::new();
-    keywords.insert("and".to_string(), TokenType::And);
-    keywords.insert("class".to_string(), TokenType::Class);
-    keywords.insert("else".to_string(), TokenType::Else);
-    keywords.insert("false".to_string(), TokenType::False);
-    keywords.insert("for".to_string(), TokenType::For);
-    keywords.insert("fun".to_string(), TokenType::Fun);
-    keywords.insert("if".to_string(), TokenType::If);
-    keywords.insert("nil".to_string(), TokenType::Nil);
-    keywords.insert("or".to_string(), TokenType::Or);
-    keywords.insert("print".to_string(), TokenType::Print);
-    keywords.insert("return".to_string(), TokenType::Return);
-    keywords.insert("super".to_string(), TokenType::Super);
-    keywords.insert("this".to_string(), TokenType::This);
-    keywords.insert("true".to_string(), TokenType::True);
-    keywords.insert("var".to_string(), TokenType::Var);
-    keywords.insert("while".to_string(), TokenType::While);
-    keywords
-}
+    // Ctrl-C at the prompt is a key the editor reads; while code runs it is a
+    // signal that stops it and gets back to the prompt. The handler has to be
+    // set after creating the editor, which installs its own.
+    let interrupt = Arc::new(AtomicBool::new(false));
+    let handler_flag = Arc::clone(&interrupt);
+    ctrlc::set_handler(move || handler_flag.store(true, Ordering::Relaxed))?;
 
-pub struct Scanner {
-    source: String,
-    tokens: Vec<Token>,
-    start: usize,
-    current: usize,
-    line: usize,
-    keywords: HashMap<String, TokenType>,
-}
-
-impl Scanner {
-    pub fn new(source: String) -> Self {
-        Scanner {
-            source,
-            tokens: vec![],
-            start: 0,
-            current: 0,
-            line: 1,
-            keywords: load_keywords(),
-        }
-    }
+    let mut interpreter = interpreter(allow_io);
+    interpreter.set_interrupt(Arc::clone(&interrupt));
 
-    pub fn scan_tokens(&mut self) -> &[Token] {
-        while self.source.len() > self.current {
-            self.start = self.current;
-            self.scan_token();
+    let mut source = String::new();
+    loop {
+        if let Some(helper) = editor.helper_mut() {
+            helper.set_names(interpreter.names());
         }
 
-        self.tokens.push(Token {
-            token_type: TokenType::EOF,
-            lexeme: "".to_string(),
-            literal: Value::None,
-            line: self.line,
-        });
-
-        &self.tokens[..]
-    }
-
-    fn scan_token(&mut self) {
-        let content = self.source.chars().collect::<Vec<_>>();
-        let char = content[self.current];
-        self.current += 1;
-
-        match char {
-            '(' => self.add_token(TokenType::LeftParen),
-            ')' => self.add_token(TokenType::RightParen),
-            '{' => self.add_token(TokenType::LeftBrace),
-            '}' => self.add_token(TokenType::RightBrace),
-            ',' => self.add_token(TokenType::Comma),
-            '.' => self.add_token(TokenType::Dot),
-            '-' => self.add_token(TokenType::Minus),
-            '+' => self.add_token(TokenType::Plus),
-            ';' => self.add_token(TokenType::Semicolon),
-            '*' => self.add_token(TokenType::Star),
-            '!' => {
-                let c = if self.check('=', &content) {
-                    TokenType::BangEqual
-                } else {
-                    TokenType::Bang
-                };
-                self.add_token(c);
+        let prompt = if source.is_empty() { ">> " } else { ".. " };
+        let line = match editor.readline(prompt) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => {
+                source.clear();
+                continue;
             }
-            '=' => {
-                let c = if self.check('=', &content) {
-                    TokenType::EqualEqual
-                } else {
-                    TokenType::Equal
-                };
-                self.add_token(c);
+            Err(ReadlineError::Eof) => {
+                println!("Bye!");
+                break;
             }
-            '<' => {
-                let c = if self.check('=', &content) {
-                    TokenType::LessEqual
-                } else {
-                    TokenType::Less
-                };
-                self.add_token(c);
-            }
-            '>' => {
-                let c = if self.check('=', &content) {
-                    TokenType::GreaterEqual
-                } else {
-                    TokenType::Greater
-                };
-                self.add_token(c);
-            }
-            '/' => {
-                if self.check('/', &content) {
-                    while self.peek(&content) != '\n' && self.source.len() > self.current {
-                        let _ = content[self.current];
-                        self.current += 1;
-                    }
-                } else {
-                    self.add_token(TokenType::Slash);
-                };
-            }
-            ' ' => (),
-            '\r' => (),
-            '\t' => (),
-            '\n' => self.line += 1,
-            '"' => self.handle_string(&content),
-            '0'..='9' => self.handle_number(&content),
-            _ => {
-                if char.is_alphabetic() || char == '_' {
-                    self.identifier(&content)
-                } else {
-                    report_error(
-                        self.line,
-                        "",
-                        "Caracter desconocido",
-                    )
-                }
+            Err(error) => return Err(error.into()),
+        };
+        // A Ctrl-C that came after the last run finished is not for the next.
+        interrupt.store(false, Ordering::Relaxed);
+
+        if source.is_empty() {
+            if let Some(command) = line.trim().strip_prefix(':') {
+                editor.add_history_entry(line.trim())?;
+                meta_command(&mut interpreter, command, format);
+                continue;
             }
         }
-    }
 
-    fn handle_number(&mut self, vec: &[char]) {
-        while self.peek(vec).is_ascii_digit() {
-            let _ = vec[self.current];
-            self.current += 1;
+        let blank = line.trim().is_empty();
+        source.push_str(&line);
+        source.push('\n');
+        if !blank && is_incomplete(&source) {
+            continue;
         }
-        if self.peek(vec) == '.' && self.peek_next(vec).is_ascii_digit() {
-            let _ = vec[self.current];
-            self.current += 1;
-            while self.peek(vec).is_ascii_digit() {
-                let _ = vec[self.current];
-                self.current += 1;
-            }
+        if !source.trim().is_empty() {
+            editor.add_history_entry(source.trim_end())?;
         }
 
-        let string = vec[self.start..self.current].iter().collect::<String>();
-        let number = string
-            .trim()
-            .parse::<f64>()
-            .expect("La conversión de string a float ha fallado");
-        self.add_token_literal(TokenType::Number, Value::Number(number))
-    }
-
-    fn handle_string(&mut self, vec: &[char]) {
-        while self.peek(vec) != '"' && self.source.len() > self.current {
-            if self.peek(vec) == '\n' {
-                self.line += 1;
-            }
-            let _ = vec[self.current];
-            self.current += 1;
-        }
-        if self.source.len() <= self.current {
-            report_error(self.line, "", "String sin cerrar");
-            return;
+        let result = match format {
+            Some(format) => print_line_ast(&source, format).map(|_| None),
+            None => run_line(&mut interpreter, &source),
+        };
+        if let Ok(Some(value)) = result {
+            println!("{value}");
         }
-        let _ = vec[self.current];
-        self.current += 1;
-
-        let text = &self.source[self.start + 1..self.current - 1].trim();
-        self.add_token_literal(TokenType::String, Value::String(text.to_string()));
-    }
-
-    fn add_token(&mut self, token_type: TokenType) {
-        self.add_token_literal(token_type, Value::None);
+        source.clear();
     }
 
-    fn add_token_literal(&mut self, token_type: TokenType, literal: Value) {
-        let lexeme = &self.source[self.start..self.current];
-        self.tokens.push(Token {
-            token_type,
-            lexeme: lexeme.to_string(),
-            literal,
-            line: self.line,
-        })
+    if let Some(history) = &history {
+        editor.save_history(history)?;
     }
+    Ok(())
+}
 
-    fn check(&mut self, expected: char, vec: &[char]) -> bool {
-        if self.source.len() <= self.current {
-            return false;
+const REPL_HELP: &str = "\
+Commands:
+  :tokens <code>  shows the tokens of the code
+  :ast <code>     shows the syntax tree of the code
+  :env            lists the variables defined and their values
+  :load <file>    runs a file in this session
+  :reset          forgets everything defined in the session
+  :help           shows this help";
+
+// Commands for looking inside the session, typed as `:name argument`.
+fn meta_command(interpreter: &mut Interpreter, command: &str, format: Option<Format>) {
+    let (name, argument) = command.split_once(char::is_whitespace).unwrap_or((command, ""));
+    let argument = argument.trim();
+
+    match name {
+        "tokens" => {
+            let _ = print_tokens(argument);
         }
-        if vec[self.current] != expected {
-            return false;
+        "ast" => {
+            let _ = print_line_ast(argument, format.unwrap_or(Format::SExpr));
         }
-
-        self.current += 1;
-        true
-    }
-
-    fn peek(&self, vec: &[char]) -> char {
-        if self.source.len() <= self.current {
-            return '\0';
+        "env" => {
+            for (name, value) in interpreter.bindings() {
+                println!("{name} = {value}");
+            }
         }
-        vec[self.current]
+        "load" => match fs::read_to_string(argument) {
+            Ok(content) => {
+                let _ = run(interpreter, &content);
+            }
+            Err(error) => eprintln!("Error: {argument}: {error}"),
+        },
+        "reset" => interpreter.reset(),
+        "help" => println!("{REPL_HELP}"),
+        _ => eprintln!("Unknown command ':{name}', use :help to see the available ones"),
     }
+}
 
-    fn peek_next(&self, vec: &[char]) -> char {
-        if self.current + 1 >= self.source.len() {
-            return '\0';
-        }
-        vec[self.current + 1]
+// Whether `source` stops in the middle of something: an open string, brace
+// or parenthesis, or a statement still missing its `;`. That is, every error
+// found is at the end of the input. A bare expression is complete, it is what
+// the REPL echoes.
+fn is_incomplete(source: &str) -> bool {
+    let mut scanner = Scanner::new(source.to_string());
+    let tokens = scanner.scan_tokens().to_vec();
+    if !scanner.errors().is_empty() {
+        return scanner.errors().iter().all(|error| error.at_end);
     }
 
-    fn identifier(&mut self, vec: &[char]) {
-        while self.peek(vec).is_alphanumeric() || self.peek(vec) == '_' {
-            let _ = vec[self.current];
-            self.current += 1;
-        }
-        let binding = vec[self.start..self.current].iter().collect::<String>();
-        let text = binding.trim();
-
-        match self.keywords.get(text) {
-            Some(t) => self.add_token(t.clone()),
-            None => self.add_token(TokenType::Identifier),
-        };
+    if rlox::is_expression(source) {
+        return false;
     }
-}
-// -----------------------------------------------------------------------------------------
-// PARSER
-// -----------------------------------------------------------------------------------------
-/*
-
-expression  →  equality ;
-equality    →  comparison ( ( "!=" | "==" ) comparison )* ;
-comparison  →  term ( ( ">" | ">=" | "<" | "<=" ) term )* ;
-term        →  factor ( ( "-" | "+" ) factor )* ;
-factor      →  unary ( ( "/" | "*" ) unary )* ;
-unary       →  ( "!" | "-" ) unary
-                    | primary ;
-primary     →  NUMBER | STRING | "true" | "false" | "nil"
-                    | "(" expression ")" ;
-*/
-
-#[derive(Debug)]
-struct ParserError {}
-
-impl ParserError {
-    pub fn new() -> Self {
-        Self {}
+    match Parser::new(tokens, &mut Ast::new()).parse() {
+        Ok(_) => false,
+        Err(errors) => errors.iter().all(|error| error.at_end),
     }
 }
 
-struct Parser {
-    tokens: Vec<Token>,
-    current: usize,
+// Why a run stopped. The errors themselves have been reported by then.
+enum Failure {
+    Static,
+    Runtime,
 }
 
-impl Parser {
-    pub fn default() -> Self {
-        Self { tokens: vec![], current: 0}
-    }
-
-    pub fn new(tokens: Vec<Token>) -> Self {
-        Self {tokens, current: 0}
-    }
-
-    fn equals(&mut self, tok_types: &[TokenType]) -> bool {
-        for tok_type in tok_types {
-            if self.check(tok_type) {
-                self.advance();
-                return true;
-            }
-        }
-        false
-    }
-
-    fn check(&self, tok_type: &TokenType) -> bool {
-        if self.is_at_end() { return false; }
-
-        self.peek().token_type == *tok_type
-    }
-
-    fn advance(&mut self) -> Token {
-        if !self.is_at_end() { self.current += 1 }
-        self.previous()
-    }
-
-    fn is_at_end(&self) -> bool {
-        self.peek().token_type == TokenType::EOF
-    }
-
-    fn peek(&self) -> Token {
-        self.tokens.get(self.current).expect("Expects a token").clone()
-    }
-
-    fn previous(&self) -> Token {
-        self.tokens.get(self.current - 1).expect("Expects a token").clone()
+// Prints every token, even when there are errors, which are reported after.
+fn print_tokens(source: &str) -> Result<(), Failure> {
+    let mut scanner = Scanner::new(source.to_string());
+    for token in scanner.scan_tokens() {
+        println!("{token}");
     }
-
-    fn error(&self, token: Token, message: &str) -> ParserError {
-        error(token, message);
-        ParserError::new()
+    match scanner.errors() {
+        [] => Ok(()),
+        errors => Err(report_syntax_errors(errors)),
     }
+}
 
-    fn consume(&mut self, tok_type: TokenType) -> Result<Token, ()> {
-        if self.equals(&[tok_type]) {
-            return Ok(self.advance());
-        }
-        Err(())
-    }
-
-    fn synchronize(&mut self) {
-        self.advance();
-
-        while !self.is_at_end() {
-            if self.previous().token_type == TokenType::Semicolon {
-                return;
-            }
-
-            match self.peek().token_type {
-                TokenType::Class | TokenType::Fun | TokenType::Var | TokenType::For | TokenType::If | TokenType::While | TokenType::Print | TokenType::Return => return,
-                _ => (),
-            }
-
-            self.advance();
-        }
-    } 
-
-    fn primary(&mut self) -> Result<Box<dyn expr::Expr>, ParserError> {
-        if self.equals(&[TokenType::False]) { 
-            return Ok(Box::new(expr::Literal::new(Value::Boolean(false))));
-        }
-        
-        if self.equals(&[TokenType::True]) { 
-            return Ok(Box::new(expr::Literal::new(Value::Boolean(true))));
-        }
-        
-        if self.equals(&[TokenType::Nil]) { 
-            return Ok(Box::new(expr::Literal::new(Value::None)));
-        }
-
-        if self.equals(&[TokenType::LeftParen]) { 
-            let expr = self.expression();
-            match self.consume(TokenType::RightParen) {
-                Err(()) => self.error(self.peek(), "Expect ')' after expression."),
-                _ => ParserError::new()
-            };
-
-            return Ok(Box::new(expr::Grouping::new(expr?)));
-        }
+// Finds the errors there are without running anything.
+fn check(source: &str) -> Result<(), Failure> {
+    rlox::parse(source, &mut Ast::new())
+        .map(|_| ())
+        .map_err(|errors| report_syntax_errors(&errors))
+}
 
-        Err(self.error(self.peek(), "Expect expression."))
+// The tree as written: unlike `rlox::parse`, no `Desugar` and no `Resolver`,
+// so `for` loops show up as such and spans are the ones in the source.
+fn parse_source(source: &str, ast: &mut Ast) -> Result<Vec<StmtId>, Failure> {
+    let mut scanner = Scanner::new(source.to_string());
+    let tokens = scanner.scan_tokens().to_vec();
+    if !scanner.errors().is_empty() {
+        return Err(report_syntax_errors(scanner.errors()));
     }
+    Parser::new(tokens, ast).parse().map_err(|errors| report_syntax_errors(&errors))
+}
 
-    fn unary(&mut self) -> Result<Box<dyn expr::Expr>, ParserError> {
-        if self.equals(&[TokenType::Bang, TokenType::Minus]) {
-            let operator = self.previous();
-            let right = self.unary()?;
-            return Ok(Box::new(expr::Unary::new(operator, right)));
-        }
-
-        Ok(self.primary())?
+fn print_ast(source: &str, format: Format) -> Result<(), Failure> {
+    let mut ast = Ast::new();
+    let statements = parse_source(source, &mut ast)?;
+    if let Err(error) = AstPrinter::new(&ast, format).print(&statements, &mut io::stdout()) {
+        eprintln!("Error: {error}");
     }
+    Ok(())
+}
 
-    fn factor(&mut self) -> Result<Box<dyn expr::Expr>, ParserError> {
-        let mut expr = self.unary()?;
-
-        while self.equals(&[TokenType::Slash, TokenType::Star]) {
-            let operator = self.previous();
-            let right = self.unary()?;
-            expr = Box::new(expr::Binary::new(expr, operator, right))
-        }
-        Ok(expr)
+// Like `print_ast`, but a single expression with no `;` is printed as such,
+// the way `eval` takes what is typed at the REPL.
+fn print_line_ast(source: &str, format: Format) -> Result<(), Failure> {
+    if !rlox::is_expression(source) {
+        return print_ast(source, format);
     }
 
-    fn term(&mut self) -> Result<Box<dyn expr::Expr>, ParserError> {
-        let mut expr = self.factor()?;
-
-        while self.equals(&[TokenType::Minus, TokenType::Plus]) {
-            let operator = self.previous();
-            let right = self.factor()?;
-            expr = Box::new(expr::Binary::new(expr, operator, right))
-        }
-        Ok(expr)
+    let tokens = Scanner::new(source.to_string()).scan_tokens().to_vec();
+    let mut ast = Ast::new();
+    let expr = Parser::new(tokens, &mut ast)
+        .parse_expression()
+        .map_err(|error| report_syntax_errors(&[error]))?;
+    if let Err(error) = AstPrinter::new(&ast, format).print_expr(expr, &mut io::stdout()) {
+        eprintln!("Error: {error}");
     }
+    Ok(())
+}
 
-    fn comparison(&mut self) -> Result<Box<dyn expr::Expr>, ParserError> {
-        let mut expr = self.term()?;
-
-        while self.equals(&[TokenType::Greater, TokenType::GreaterEqual, TokenType::Less, TokenType::LessEqual]) {
-            let operator = self.previous();
-            let right = self.term()?;
-            expr = Box::new(expr::Binary::new(expr, operator, right));
-        }
-        Ok(expr)
-    }
+fn run(interpreter: &mut Interpreter, source: &str) -> Result<(), Failure> {
+    interpreter.run(source).map_err(report)
+}
 
-    fn expression(&mut self) -> Result<Box<dyn expr::Expr>, ParserError> {
-        Ok(self.equality())?
-    } 
+// Like `run`, but the input may also be a single expression without `;`,
+// whose value is returned so the REPL can echo it.
+fn run_line(interpreter: &mut Interpreter, source: &str) -> Result<Option<Value>, Failure> {
+    let value = interpreter.eval(source).map_err(report)?;
+    Ok(rlox::is_expression(source).then_some(value))
+}
 
-    fn equality(&mut self) -> Result<Box<dyn expr::Expr>, ParserError> {
-        let mut expr = self.comparison()?;
-        while self.equals(&[TokenType::BangEqual, TokenType::EqualEqual]) {
-            let operator = self.previous();
-            let right = self.comparison()?;
-            expr = Box::new(expr::Binary::new(expr, operator, right));
+fn report(error: rlox::Error) -> Failure {
+    match error {
+        rlox::Error::Syntax(errors) => report_syntax_errors(&errors),
+        rlox::Error::Runtime(error) => {
+            eprintln!("{error}");
+            Failure::Runtime
         }
-        Ok(expr)
     }
+}
 
-    pub fn parse(&mut self) -> Box<dyn expr::Expr> {
-        self.expression().expect("Error during parsing")
+fn report_syntax_errors(errors: &[SyntaxError]) -> Failure {
+    for error in errors {
+        eprintln!("{error}");
     }
+    Failure::Static
 }
@@ -0,0 +1,99 @@
+use std::borrow::Cow;
+use std::fs;
+use std::path::PathBuf;
+
+use colored::Colorize;
+use rustyline::completion::Completer;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+
+use rlox::load_keywords;
+
+pub type LoxEditor = Editor<LoxHelper, DefaultHistory>;
+
+/// Line editor for the REPL: emacs style editing, history with Ctrl-R search
+/// and tab completion of keywords and of the names defined in the session.
+pub fn editor() -> rustyline::Result<LoxEditor> {
+    let mut editor = LoxEditor::new()?;
+    editor.set_helper(Some(LoxHelper::new()));
+    Ok(editor)
+}
+
+/// Where the history is kept between sessions: `$XDG_DATA_HOME/rlox/history`
+/// (or the platform's data directory), falling back to `~/.rlox_history`.
+pub fn history_path() -> Option<PathBuf> {
+    if let Some(data) = dirs::data_dir() {
+        let dir = data.join("rlox");
+        if fs::create_dir_all(&dir).is_ok() {
+            return Some(dir.join("history"));
+        }
+    }
+    dirs::home_dir().map(|home| home.join(".rlox_history"))
+}
+
+pub struct LoxHelper {
+    keywords: Vec<String>,
+    names: Vec<String>,
+}
+
+impl LoxHelper {
+    fn new() -> Self {
+        let mut keywords = load_keywords().into_keys().collect::<Vec<_>>();
+        keywords.sort();
+        LoxHelper {
+            keywords,
+            names: vec![],
+        }
+    }
+
+    /// Names to complete besides the keywords, refreshed after every run.
+    pub fn set_names(&mut self, names: Vec<String>) {
+        self.names = names;
+    }
+}
+
+impl Completer for LoxHelper {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = line[..pos]
+            .char_indices()
+            .rev()
+            .take_while(|(_, char)| char.is_alphanumeric() || *char == '_')
+            .last()
+            .map_or(pos, |(index, _)| index);
+        let prefix = &line[start..pos];
+        if prefix.is_empty() {
+            return Ok((pos, vec![]));
+        }
+
+        let mut candidates = self
+            .keywords
+            .iter()
+            .chain(&self.names)
+            .filter(|word| word.starts_with(prefix))
+            .cloned()
+            .collect::<Vec<_>>();
+        candidates.sort();
+        candidates.dedup();
+        Ok((start, candidates))
+    }
+}
+
+impl Highlighter for LoxHelper {
+    fn highlight_prompt<'b, 's: 'b, 'p: 'b>(&'s self, prompt: &'p str, _default: bool) -> Cow<'b, str> {
+        Cow::Owned(prompt.bold().green().to_string())
+    }
+}
+
+impl Hinter for LoxHelper {
+    type Hint = String;
+}
+
+// Multi-line input is handled by the REPL itself, see `is_incomplete`.
+impl Validator for LoxHelper {}
+
+impl Helper for LoxHelper {}
@@ -0,0 +1,49 @@
+use crate::ast::{Ast, StmtId};
+use crate::expr::{self, Fold as _};
+use crate::stmt::{self, Fold};
+use crate::Value;
+
+// Lowers surface syntax into the smaller core the later passes understand:
+//
+//   for (init; cond; incr) body   →   { init; while (cond) body }
+//
+// with `incr` kept as the increment of the `while`, which runs after the body
+// even when it ends with `continue`.
+#[derive(Default)]
+pub struct Desugar {}
+
+impl Desugar {
+    pub fn new() -> Self {
+        Desugar {}
+    }
+
+    pub fn run(&mut self, ast: &mut Ast, statements: &[StmtId]) -> Vec<StmtId> {
+        statements
+            .iter()
+            .map(|statement| self.fold_stmt(ast, *statement))
+            .collect()
+    }
+}
+
+impl expr::Fold for Desugar {}
+
+impl stmt::Fold for Desugar {
+    fn fold_for_stmt(&mut self, ast: &mut Ast, id: StmtId, stmt: &stmt::For) -> StmtId {
+        let span = ast.stmt_span(id);
+        let initializer = stmt.initializer.map(|s| self.fold_stmt(ast, s));
+        let condition = stmt.condition.map(|e| self.fold_expr(ast, e));
+        let increment = stmt.increment.map(|e| self.fold_expr(ast, e));
+        let body = self.fold_stmt(ast, stmt.body);
+
+        let condition = match condition {
+            Some(condition) => condition,
+            None => ast.alloc_expr(expr::Literal::new(Value::Boolean(true)), span),
+        };
+        let mut lowered = ast.alloc_stmt(stmt::While::new(condition, body, increment), span);
+
+        if let Some(initializer) = initializer {
+            lowered = ast.alloc_stmt(stmt::Block::new(vec![initializer, lowered]), span);
+        }
+        lowered
+    }
+}
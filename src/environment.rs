@@ -0,0 +1,77 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::interpreter::RuntimeError;
+use crate::{Token, Value};
+
+/// Variables of one scope. Blocks get a new environment pointing to the one
+/// they were entered from; lookups walk out until they find the name.
+#[derive(Default)]
+pub struct Environment {
+    values: HashMap<String, Value>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_enclosing(enclosing: Rc<RefCell<Environment>>) -> Self {
+        Environment {
+            values: HashMap::new(),
+            enclosing: Some(enclosing),
+        }
+    }
+
+    // Redefining a name in the same scope is allowed, like at the top level
+    // of a REPL session.
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    /// Every visible variable and its value, sorted by name. Shadowed outer
+    /// variables are left out.
+    pub fn bindings(&self) -> Vec<(String, Value)> {
+        let mut bindings = self
+            .values
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect::<Vec<_>>();
+        if let Some(enclosing) = &self.enclosing {
+            for (name, value) in enclosing.borrow().bindings() {
+                if !self.values.contains_key(&name) {
+                    bindings.push((name, value));
+                }
+            }
+        }
+        bindings.sort_by(|(a, _), (b, _)| a.cmp(b));
+        bindings
+    }
+
+    pub fn get(&self, name: &Token) -> Result<Value, RuntimeError> {
+        if let Some(value) = self.values.get(&name.lexeme) {
+            return Ok(value.clone());
+        }
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow().get(name),
+            None => Err(undefined(name)),
+        }
+    }
+
+    pub fn assign(&mut self, name: &Token, value: Value) -> Result<(), RuntimeError> {
+        if let Some(slot) = self.values.get_mut(&name.lexeme) {
+            *slot = value;
+            return Ok(());
+        }
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().assign(name, value),
+            None => Err(undefined(name)),
+        }
+    }
+}
+
+fn undefined(name: &Token) -> RuntimeError {
+    RuntimeError::new(name, &format!("Undefined variable '{}'.", name.lexeme))
+}
@@ -0,0 +1,76 @@
+use std::fmt;
+
+use crate::interpreter::RuntimeError;
+use crate::{Token, TokenType};
+
+/// Error found before running: by the scanner, the parser or the `Resolver`.
+/// They all keep going after one so a single run reports as many as possible;
+/// whoever drives them decides how to report them.
+#[derive(Debug, Clone)]
+pub struct SyntaxError {
+    pub line: usize,
+    pub place: String,
+    pub message: String,
+    /// The input ran out before it was complete, so more of it could fix the
+    /// error. The REPL uses this to ask for another line.
+    pub at_end: bool,
+}
+
+impl SyntaxError {
+    pub(crate) fn at(token: &Token, message: &str) -> Self {
+        let at_end = token.token_type == TokenType::EOF;
+        let place = if at_end {
+            " at end".to_string()
+        } else {
+            format!(" at '{}'", token.lexeme)
+        };
+        SyntaxError {
+            line: token.line,
+            place,
+            message: message.to_string(),
+            at_end,
+        }
+    }
+}
+
+impl core::fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "[{}] | Error{}: {}", self.line, self.place, self.message)
+    }
+}
+
+impl std::error::Error for SyntaxError {}
+
+/// Why running some source failed.
+#[derive(Debug)]
+pub enum Error {
+    /// The source doesn't scan, parse or resolve, so none of it ran.
+    Syntax(Vec<SyntaxError>),
+    Runtime(RuntimeError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Syntax(errors) => {
+                let errors = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+                write!(f, "{}", errors.join("\n"))
+            }
+            Error::Runtime(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<Vec<SyntaxError>> for Error {
+    fn from(errors: Vec<SyntaxError>) -> Self {
+        Error::Syntax(errors)
+    }
+}
+
+impl From<RuntimeError> for Error {
+    fn from(error: RuntimeError) -> Self {
+        Error::Runtime(error)
+    }
+}
@@ -0,0 +1,457 @@
+use crate::ast::{Ast, ExprId, StmtId};
+use crate::expr;
+use crate::stmt;
+use crate::{Token, TokenType, Value};
+
+const INDENT: &str = "    ";
+
+// Binding power of each level of the expression grammar, loosest first.
+const ASSIGNMENT: u8 = 1;
+const OR: u8 = 2;
+const AND: u8 = 3;
+const EQUALITY: u8 = 4;
+const COMPARISON: u8 = 5;
+const TERM: u8 = 6;
+const FACTOR: u8 = 7;
+const UNARY: u8 = 8;
+const CALL: u8 = 9;
+const PRIMARY: u8 = 10;
+
+/// Prints a parsed program back as canonical Lox: one statement per line,
+/// four space indentation, spaces around binary operators and only the
+/// parentheses the grammar needs. Comments come from a token stream scanned
+/// with `Scanner::with_comments` and are put back before the statement that
+/// followed them, or after it when they shared its last line.
+///
+/// Works on the tree straight out of the `Parser`, before `Desugar` runs, so
+/// `for` loops are printed as written.
+pub struct Formatter<'a> {
+    ast: &'a Ast,
+    comments: Vec<Token>,
+    next_comment: usize,
+    newlines: Vec<usize>,
+    indent: usize,
+}
+
+impl<'a> Formatter<'a> {
+    pub fn new(ast: &'a Ast, source: &str, comments: Vec<Token>) -> Self {
+        // Spans are byte offsets, same as the scanner.
+        let newlines = source
+            .bytes()
+            .enumerate()
+            .filter(|(_, byte)| *byte == b'\n')
+            .map(|(offset, _)| offset)
+            .collect();
+
+        Formatter {
+            ast,
+            comments,
+            next_comment: 0,
+            newlines,
+            indent: 0,
+        }
+    }
+
+    pub fn format(&mut self, statements: &[StmtId]) -> String {
+        let mut lines = self.sequence(statements, usize::MAX);
+        if lines.is_empty() {
+            return String::new();
+        }
+        lines.push(String::new());
+        lines.join("\n")
+    }
+
+    fn expr(&mut self, expr: ExprId) -> String {
+        self.ast.accept_expr(expr, self)
+    }
+
+    fn stmt(&mut self, stmt: StmtId) -> String {
+        self.ast.accept_stmt(stmt, self)
+    }
+
+    // Prints `expr`, parenthesized when it binds looser than its context
+    // allows.
+    fn operand(&mut self, expr: ExprId, min_precedence: u8) -> String {
+        let printed = self.expr(expr);
+        if self.precedence(expr) < min_precedence {
+            format!("({printed})")
+        } else {
+            printed
+        }
+    }
+
+    fn precedence(&self, expr: ExprId) -> u8 {
+        let node = self.ast.expr(expr);
+        let node = node.as_any();
+        if let Some(grouping) = node.downcast_ref::<expr::Grouping>() {
+            // Groupings are dropped, what matters is what they wrap.
+            return self.precedence(grouping.expression);
+        }
+        if let Some(binary) = node.downcast_ref::<expr::Binary>() {
+            return binary_precedence(&binary.operator.token_type);
+        }
+        if let Some(logical) = node.downcast_ref::<expr::Logical>() {
+            return binary_precedence(&logical.operator.token_type);
+        }
+        if node.is::<expr::Assign>() {
+            return ASSIGNMENT;
+        }
+        if node.is::<expr::Unary>() {
+            return UNARY;
+        }
+        if node.is::<expr::IndexSet>() {
+            return ASSIGNMENT;
+        }
+        if node.is::<expr::Call>() || node.is::<expr::Get>() || node.is::<expr::Index>() {
+            return CALL;
+        }
+        PRIMARY
+    }
+
+    fn indentation(&self) -> String {
+        INDENT.repeat(self.indent)
+    }
+
+    fn line_of(&self, offset: usize) -> usize {
+        self.newlines.partition_point(|newline| *newline < offset) + 1
+    }
+
+    // Statements of a block (or the whole program) one per line, each preceded
+    // by the comments found before it. Runs of blank lines in the source are
+    // kept as a single one. `end` is where the block closes: comments before
+    // it are still printed inside.
+    fn sequence(&mut self, statements: &[StmtId], end: usize) -> Vec<String> {
+        let mut lines = vec![];
+        let mut last_line = None;
+
+        for statement in statements {
+            let span = self.ast.stmt_span(*statement);
+            self.comments_before(span.start, &mut lines, &mut last_line);
+
+            let first_line = self.line_of(span.start);
+            if last_line.is_some_and(|last| first_line > last + 1) {
+                lines.push(String::new());
+            }
+
+            let mut printed = format!("{}{}", self.indentation(), self.stmt(*statement));
+            let end_line = self.line_of(span.end.saturating_sub(1));
+            if let Some(comment) = self.trailing_comment(end_line) {
+                printed = format!("{printed} {comment}");
+            }
+            lines.push(printed);
+            last_line = Some(end_line);
+        }
+
+        self.comments_before(end, &mut lines, &mut last_line);
+        lines
+    }
+
+    fn comments_before(&mut self, offset: usize, lines: &mut Vec<String>, last_line: &mut Option<usize>) {
+        while let Some(comment) = self.comments.get(self.next_comment) {
+            if comment.start >= offset {
+                break;
+            }
+            if last_line.is_some_and(|last| comment.line > last + 1) {
+                lines.push(String::new());
+            }
+            lines.push(format!("{}{}", self.indentation(), comment.lexeme.trim_end()));
+            *last_line = Some(comment.line);
+            self.next_comment += 1;
+        }
+    }
+
+    fn trailing_comment(&mut self, line: usize) -> Option<String> {
+        let comment = self.comments.get(self.next_comment)?;
+        if comment.line != line {
+            return None;
+        }
+        self.next_comment += 1;
+        Some(comment.lexeme.trim_end().to_string())
+    }
+
+    // Body of an `if`, `while` or `for`: blocks open on the same line, any
+    // other statement goes indented on the next one.
+    fn body(&mut self, body: StmtId) -> String {
+        if self.ast.stmt(body).as_any().is::<stmt::Block>() {
+            return format!(" {}", self.stmt(body));
+        }
+
+        self.indent += 1;
+        let mut lines = vec![];
+        self.comments_before(self.ast.stmt_span(body).start, &mut lines, &mut None);
+        lines.push(format!("{}{}", self.indentation(), self.stmt(body)));
+        self.indent -= 1;
+        format!("\n{}", lines.join("\n"))
+    }
+}
+
+fn binary_precedence(operator: &TokenType) -> u8 {
+    match operator {
+        TokenType::Or => OR,
+        TokenType::And => AND,
+        TokenType::BangEqual | TokenType::EqualEqual => EQUALITY,
+        TokenType::Greater | TokenType::GreaterEqual | TokenType::Less | TokenType::LessEqual => COMPARISON,
+        TokenType::Minus | TokenType::Plus => TERM,
+        TokenType::Slash | TokenType::Star => FACTOR,
+        _ => PRIMARY,
+    }
+}
+
+// Lox strings have no escapes, so the contents go back verbatim.
+fn literal_to_source(literal: &Value) -> String {
+    match literal {
+        Value::None => "nil".to_string(),
+        Value::String(string) => format!("\"{string}\""),
+        Value::Number(number) => number.to_string(),
+        Value::Boolean(boolean) => boolean.to_string(),
+        Value::Native(_) | Value::List(_) | Value::Map(_) => literal.to_string(),
+    }
+}
+
+impl expr::Visitor<String> for Formatter<'_> {
+    fn visit_assign_expr(&mut self, _id: ExprId, expr: &expr::Assign) -> String {
+        // Right associative: `a = b = c` needs no parentheses.
+        let value = self.operand(expr.value, ASSIGNMENT);
+        format!("{} = {value}", expr.name.lexeme)
+    }
+
+    fn visit_binary_expr(&mut self, _id: ExprId, expr: &expr::Binary) -> String {
+        // Left associative: only the right operand needs parentheses at the
+        // same level.
+        let precedence = binary_precedence(&expr.operator.token_type);
+        let left = self.operand(expr.left, precedence);
+        let right = self.operand(expr.right, precedence + 1);
+        format!("{left} {} {right}", expr.operator.lexeme)
+    }
+
+    fn visit_call_expr(&mut self, _id: ExprId, expr: &expr::Call) -> String {
+        let callee = self.operand(expr.callee, CALL);
+        let arguments = expr
+            .arguments
+            .iter()
+            .map(|argument| self.expr(*argument))
+            .collect::<Vec<_>>();
+        format!("{callee}({})", arguments.join(", "))
+    }
+
+    fn visit_get_expr(&mut self, _id: ExprId, expr: &expr::Get) -> String {
+        let object = self.operand(expr.object, CALL);
+        format!("{object}.{}", expr.name.lexeme)
+    }
+
+    fn visit_grouping_expr(&mut self, _id: ExprId, expr: &expr::Grouping) -> String {
+        self.expr(expr.expression)
+    }
+
+    fn visit_index_expr(&mut self, _id: ExprId, expr: &expr::Index) -> String {
+        let object = self.operand(expr.object, CALL);
+        format!("{object}[{}]", self.expr(expr.index))
+    }
+
+    fn visit_index_set_expr(&mut self, _id: ExprId, expr: &expr::IndexSet) -> String {
+        let object = self.operand(expr.object, CALL);
+        let index = self.expr(expr.index);
+        let value = self.operand(expr.value, ASSIGNMENT);
+        format!("{object}[{index}] = {value}")
+    }
+
+    fn visit_list_expr(&mut self, _id: ExprId, expr: &expr::List) -> String {
+        let elements = expr
+            .elements
+            .iter()
+            .map(|element| self.expr(*element))
+            .collect::<Vec<_>>();
+        format!("[{}]", elements.join(", "))
+    }
+
+    fn visit_literal_expr(&mut self, _id: ExprId, expr: &expr::Literal) -> String {
+        literal_to_source(&expr.value)
+    }
+
+    fn visit_logical_expr(&mut self, _id: ExprId, expr: &expr::Logical) -> String {
+        let precedence = binary_precedence(&expr.operator.token_type);
+        let left = self.operand(expr.left, precedence);
+        let right = self.operand(expr.right, precedence + 1);
+        format!("{left} {} {right}", expr.operator.lexeme)
+    }
+
+    fn visit_map_expr(&mut self, _id: ExprId, expr: &expr::Map) -> String {
+        let entries = expr
+            .keys
+            .iter()
+            .zip(&expr.values)
+            .map(|(key, value)| format!("{}: {}", self.expr(*key), self.expr(*value)))
+            .collect::<Vec<_>>();
+        format!("{{{}}}", entries.join(", "))
+    }
+
+    fn visit_unary_expr(&mut self, _id: ExprId, expr: &expr::Unary) -> String {
+        let right = self.operand(expr.right, UNARY);
+        format!("{}{right}", expr.operator.lexeme)
+    }
+
+    fn visit_variable_expr(&mut self, _id: ExprId, expr: &expr::Variable) -> String {
+        expr.name.lexeme.clone()
+    }
+}
+
+impl stmt::Visitor<String> for Formatter<'_> {
+    fn visit_block_stmt(&mut self, id: StmtId, stmt: &stmt::Block) -> String {
+        self.indent += 1;
+        let lines = self.sequence(&stmt.statements, self.ast.stmt_span(id).end);
+        self.indent -= 1;
+
+        if lines.is_empty() {
+            return "{}".to_string();
+        }
+        format!("{{\n{}\n{}}}", lines.join("\n"), self.indentation())
+    }
+
+    fn visit_break_stmt(&mut self, _id: StmtId, _stmt: &stmt::Break) -> String {
+        "break;".to_string()
+    }
+
+    fn visit_continue_stmt(&mut self, _id: StmtId, _stmt: &stmt::Continue) -> String {
+        "continue;".to_string()
+    }
+
+    // A statement that starts with `{` is a block, so one that starts with a
+    // map literal keeps it in parentheses.
+    fn visit_expression_stmt(&mut self, _id: StmtId, stmt: &stmt::Expression) -> String {
+        let expression = self.expr(stmt.expression);
+        if expression.starts_with('{') {
+            return format!("({expression});");
+        }
+        format!("{expression};")
+    }
+
+    fn visit_for_stmt(&mut self, _id: StmtId, stmt: &stmt::For) -> String {
+        // Not a statement of its own, so a map literal there needs no
+        // parentheses.
+        let initializer = match stmt.initializer {
+            Some(initializer) => match self.ast.stmt(initializer).as_any().downcast_ref::<stmt::Expression>() {
+                Some(initializer) => format!("{};", self.expr(initializer.expression)),
+                None => self.stmt(initializer),
+            },
+            None => ";".to_string(),
+        };
+        let condition = match stmt.condition {
+            Some(condition) => format!(" {};", self.expr(condition)),
+            None => ";".to_string(),
+        };
+        let increment = match stmt.increment {
+            Some(increment) => format!(" {}", self.expr(increment)),
+            None => String::new(),
+        };
+        let body = self.body(stmt.body);
+        format!("for ({initializer}{condition}{increment}){body}")
+    }
+
+    fn visit_for_in_stmt(&mut self, _id: StmtId, stmt: &stmt::ForIn) -> String {
+        let iterable = self.expr(stmt.iterable);
+        let body = self.body(stmt.body);
+        format!("for ({} in {iterable}){body}", stmt.name.lexeme)
+    }
+
+    fn visit_if_stmt(&mut self, _id: StmtId, stmt: &stmt::If) -> String {
+        let condition = self.expr(stmt.condition);
+        let then_branch = self.body(stmt.then_branch);
+        let mut printed = format!("if ({condition}){then_branch}");
+
+        if let Some(else_branch) = stmt.else_branch {
+            // `} else` after a block, `else` on its own line otherwise.
+            if self.ast.stmt(stmt.then_branch).as_any().is::<stmt::Block>() {
+                printed.push(' ');
+            } else {
+                printed.push_str(&format!("\n{}", self.indentation()));
+            }
+
+            if self.ast.stmt(else_branch).as_any().is::<stmt::If>() {
+                printed.push_str(&format!("else {}", self.stmt(else_branch)));
+            } else {
+                printed.push_str(&format!("else{}", self.body(else_branch)));
+            }
+        }
+        printed
+    }
+
+    fn visit_print_stmt(&mut self, _id: StmtId, stmt: &stmt::Print) -> String {
+        format!("print {};", self.expr(stmt.expression))
+    }
+
+    fn visit_var_stmt(&mut self, _id: StmtId, stmt: &stmt::Var) -> String {
+        match stmt.initializer {
+            Some(initializer) => {
+                let initializer = self.expr(initializer);
+                format!("var {} = {initializer};", stmt.name.lexeme)
+            }
+            None => format!("var {};", stmt.name.lexeme),
+        }
+    }
+
+    // Only `Desugar` sets the increment, and the formatter works on the tree
+    // as parsed.
+    fn visit_while_stmt(&mut self, _id: StmtId, stmt: &stmt::While) -> String {
+        let condition = self.expr(stmt.condition);
+        let body = self.body(stmt.body);
+        format!("while ({condition}){body}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Parser, Scanner};
+
+    // What `rlox fmt` writes for `source`.
+    fn format(source: &str) -> String {
+        let (comments, tokens): (Vec<Token>, Vec<Token>) = Scanner::with_comments(source.to_string())
+            .scan_tokens()
+            .iter()
+            .cloned()
+            .partition(|token| token.token_type == TokenType::Comment);
+        let mut ast = Ast::new();
+        let statements = Parser::new(tokens, &mut ast).parse().unwrap();
+        Formatter::new(&ast, source, comments).format(&statements)
+    }
+
+    #[test]
+    fn comments_are_kept_where_they_were() {
+        assert_eq!(
+            format("// Header.\nvar x=1;   // trailing\n{ print x; // inside\n  // last in the block\n}\n// at the end\n"),
+            "// Header.\nvar x = 1; // trailing\n{\n    print x; // inside\n    // last in the block\n}\n// at the end\n"
+        );
+        assert_eq!(
+            format("if (x) // why\n  print x;\nwhile (x)\n  // what\n  x = x - 1;\n"),
+            "if (x)\n    // why\n    print x;\nwhile (x)\n    // what\n    x = x - 1;\n"
+        );
+    }
+
+    #[test]
+    fn runs_of_blank_lines_become_one() {
+        assert_eq!(
+            format("\n\nprint 1;\n\n\n\nprint 2;\nprint 3;\n\n\n// note\n\n{\n\n  print 4;\n}\n\n"),
+            "print 1;\n\nprint 2;\nprint 3;\n\n// note\n\n{\n    print 4;\n}\n"
+        );
+    }
+
+    #[test]
+    fn formatting_is_idempotent() {
+        let sources = [
+            "// Header\nvar x=1;   // trailing\n\n\n{ var y = x+2; // inside\n  print y;\n  // end\n}\n",
+            "if (x) print x; else if (y) { print -x; } else print (1 + 2) * 3;\n",
+            "for (var i=0;i<2;i=i+1) print i;\nfor (;;) {}\nfor (k in {\"a\": [1, 2]}) print k;\n",
+            "while (!done) // loop\n  { xs[i] = xs.pop(); break; }\n({\"m\": 1});\nprint a = b = c or d and e;\n",
+        ];
+        for source in sources {
+            let formatted = format(source);
+            assert_eq!(format(&formatted), formatted, "formatting {source:?}");
+        }
+    }
+
+    #[test]
+    fn comments_stay_on_their_line_after_non_ascii_text() {
+        let source = "var s = \"ñandú…\";\nprint 1; // uno\n\n// dos\nprint 2;\n";
+        assert_eq!(format(source), source);
+    }
+}
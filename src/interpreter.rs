@@ -0,0 +1,835 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Stdout, Write};
+use std::mem;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::ast::{Ast, ExprId, StmtId};
+use crate::environment::Environment;
+use crate::expr;
+use crate::limits::{Limits, Usage};
+use crate::stmt;
+use crate::stdlib;
+use crate::stdlib::list::List;
+use crate::stdlib::map::Map;
+use crate::{Error, Key, NativeFunction, Parser, Scanner, Token, TokenType, Value};
+
+/// What stopped a run, so hosts can tell the script's own mistakes from
+/// the limits they put on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Something the script did wrong, like adding a number to a string.
+    Script,
+    Interrupted,
+    StepLimit,
+    StackOverflow,
+    HeapLimit,
+    TimeLimit,
+    IoDisabled,
+}
+
+#[derive(Debug)]
+pub struct RuntimeError {
+    pub kind: ErrorKind,
+    pub line: usize,
+    pub place: String,
+    pub message: String,
+}
+
+impl RuntimeError {
+    pub fn new(token: &Token, message: &str) -> Self {
+        RuntimeError {
+            kind: ErrorKind::Script,
+            line: token.line,
+            place: format!(" at '{}'", token.lexeme),
+            message: message.to_string(),
+        }
+    }
+
+    /// For natives to fail with. The interpreter points the error at the
+    /// call it came from.
+    pub fn native(message: &str) -> Self {
+        RuntimeError::at_line(0, message)
+    }
+
+    /// For errors that don't come from a particular token, like an
+    /// interruption.
+    pub fn at_line(line: usize, message: &str) -> Self {
+        RuntimeError::of_kind(ErrorKind::Script, line, message)
+    }
+
+    fn of_kind(kind: ErrorKind, line: usize, message: &str) -> Self {
+        RuntimeError {
+            kind,
+            line,
+            place: String::new(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] | Error{}: {}", self.line, self.place, self.message)
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
+// Why a statement stopped before its end: an error, or a `break` or
+// `continue` on its way out to the loop it belongs to.
+enum Unwind {
+    Error(RuntimeError),
+    Break,
+    Continue,
+}
+
+impl From<RuntimeError> for Unwind {
+    fn from(error: RuntimeError) -> Self {
+        Unwind::Error(error)
+    }
+}
+
+/// Tree-walking interpreter. It owns the `Ast` its programs are parsed into
+/// and the global environment, so a REPL can keep feeding it lines and every
+/// definition stays around for the next one.
+///
+/// `print` writes to `W`, stdout unless the interpreter is made with
+/// `with_output`.
+///
+/// Statements must have gone through `Desugar` and the `Resolver` first, as
+/// `parse` does.
+pub struct Interpreter<W = Stdout> {
+    ast: Ast,
+    environment: Rc<RefCell<Environment>>,
+    interrupt: Option<Arc<AtomicBool>>,
+    natives: Vec<Rc<NativeFunction>>,
+    limits: Limits,
+    usage: Usage,
+    /// What the script was given on the command line after its path, for
+    /// `args()`.
+    args: Rc<RefCell<Vec<String>>>,
+    output: W,
+    input: Rc<RefCell<Box<dyn BufRead>>>,
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Interpreter::with_output(io::stdout())
+    }
+}
+
+impl<W: Write> Interpreter<W> {
+    /// Interpreter whose `print` writes to `output`, e.g. a `Vec<u8>` to
+    /// capture what a script prints. Input is read from stdin. It comes with
+    /// the standard natives: `clock`, `len`, `str`, `num`, `type`, `substr`,
+    /// `upper`, `lower`, `split`, `sqrt`, `floor`, `abs`, `min`, `max`,
+    /// `random`, `seed`, `range` and `input`, and with `readFile`,
+    /// `writeFile`, `exists`, `listDir`, `getenv` and `args`, which only work
+    /// with `Limits::allow_io`.
+    pub fn with_output(output: W) -> Self {
+        let mut interpreter = Interpreter {
+            ast: Ast::new(),
+            environment: Rc::default(),
+            interrupt: None,
+            natives: vec![],
+            limits: Limits::default(),
+            usage: Usage::default(),
+            args: Rc::default(),
+            output,
+            input: Rc::new(RefCell::new(Box::new(BufReader::new(io::stdin())))),
+        };
+        stdlib::define(&mut interpreter);
+        interpreter
+    }
+
+    pub fn output(&self) -> &W {
+        &self.output
+    }
+
+    pub fn into_output(self) -> W {
+        self.output
+    }
+
+    /// Limits for every run from now on.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Makes natives that read input read from `input` instead of stdin.
+    pub fn set_input(&mut self, input: impl BufRead + 'static) {
+        *self.input.borrow_mut() = Box::new(input);
+    }
+
+    /// Where natives that read input should read from, shared so their
+    /// closures can keep it. It follows `set_input`.
+    pub fn input(&self) -> Rc<RefCell<Box<dyn BufRead>>> {
+        Rc::clone(&self.input)
+    }
+
+    /// Makes execution stop with an "Interrupted." error at the next
+    /// statement once `flag` is set, e.g. from a Ctrl-C handler. The flag is
+    /// cleared when that happens.
+    pub fn set_interrupt(&mut self, flag: Arc<AtomicBool>) {
+        self.interrupt = Some(flag);
+    }
+
+    pub fn set_args(&mut self, args: Vec<String>) {
+        *self.args.borrow_mut() = args;
+    }
+
+    /// The script's arguments, shared like `input`.
+    pub fn args(&self) -> Rc<RefCell<Vec<String>>> {
+        Rc::clone(&self.args)
+    }
+
+    /// Makes `function` callable from scripts as the global `name`. Calls
+    /// with other than `arity` arguments fail before it runs; `argument`
+    /// helps with converting the ones it gets.
+    ///
+    /// ```
+    /// use rlox::{argument, Interpreter, Value};
+    ///
+    /// let mut interpreter = Interpreter::new();
+    /// interpreter.define_native("double", 1, |arguments| {
+    ///     Ok(Value::from(argument::<f64>(arguments, 0)? * 2.0))
+    /// });
+    /// assert_eq!(interpreter.eval("double(21)").unwrap(), Value::Number(42.0));
+    /// ```
+    pub fn define_native<F>(&mut self, name: &str, arity: usize, function: F)
+    where
+        F: Fn(&[Value]) -> Result<Value, RuntimeError> + 'static,
+    {
+        self.define(NativeFunction::new(name, arity, function));
+    }
+
+    /// Like `define_native`, for natives that reach outside the interpreter
+    /// (files, the network...). `Limits::allow_io` turns them off.
+    pub fn define_io_native<F>(&mut self, name: &str, arity: usize, function: F)
+    where
+        F: Fn(&[Value]) -> Result<Value, RuntimeError> + 'static,
+    {
+        let mut native = NativeFunction::new(name, arity, function);
+        native.io = true;
+        self.define(native);
+    }
+
+    fn define(&mut self, native: NativeFunction) {
+        let native = Rc::new(native);
+        self.environment
+            .borrow_mut()
+            .define(&native.name, Value::Native(Rc::clone(&native)));
+        self.natives.push(native);
+    }
+
+    /// Forgets every definition, keeping the settings and the natives.
+    pub fn reset(&mut self) {
+        self.ast = Ast::new();
+        self.environment = Rc::new(RefCell::new(Environment::new()));
+        for native in &self.natives {
+            self.environment
+                .borrow_mut()
+                .define(&native.name, Value::Native(Rc::clone(native)));
+        }
+    }
+
+    pub fn ast_mut(&mut self) -> &mut Ast {
+        &mut self.ast
+    }
+
+    /// Variables visible from where execution is, sorted by name. Natives
+    /// are left out unless the script has put something else in their place.
+    pub fn bindings(&self) -> Vec<(String, Value)> {
+        self.environment
+            .borrow()
+            .bindings()
+            .into_iter()
+            .filter(|(name, value)| !matches!(value, Value::Native(native) if native.name == *name))
+            .collect()
+    }
+
+    /// Every name a script can use, natives included.
+    pub fn names(&self) -> Vec<String> {
+        self.environment.borrow().bindings().into_iter().map(|(name, _)| name).collect()
+    }
+
+    /// Runs `source` as a program.
+    pub fn run(&mut self, source: &str) -> Result<(), Error> {
+        let statements = crate::parse(source, &mut self.ast)?;
+        Ok(self.interpret(&statements)?)
+    }
+
+    /// Runs `source` and gives back its value. A single expression with no
+    /// `;` evaluates to its value; anything else runs as a program and gives
+    /// nil.
+    pub fn eval(&mut self, source: &str) -> Result<Value, Error> {
+        if !is_expression(source) {
+            self.run(source)?;
+            return Ok(Value::None);
+        }
+
+        let tokens = Scanner::new(source.to_string()).scan_tokens().to_vec();
+        let expression = Parser::new(tokens, &mut self.ast)
+            .parse_expression()
+            .map_err(|error| vec![error])?;
+        self.usage = Usage::starting_now(&self.limits);
+        Ok(self.evaluate(expression)?)
+    }
+
+    pub fn interpret(&mut self, statements: &[StmtId]) -> Result<(), RuntimeError> {
+        self.usage = Usage::starting_now(&self.limits);
+        for statement in statements {
+            // Only a tree that skipped the Resolver gets a jump out here.
+            let jump = match self.execute(*statement) {
+                Ok(()) => continue,
+                Err(Unwind::Error(error)) => return Err(error),
+                Err(Unwind::Break) => "break",
+                Err(Unwind::Continue) => "continue",
+            };
+            let line = self.ast.stmt_span(*statement).line;
+            return Err(RuntimeError::at_line(line, &format!("Can't use '{jump}' outside of a loop.")));
+        }
+        Ok(())
+    }
+
+    fn evaluate(&mut self, expr: ExprId) -> Result<Value, RuntimeError> {
+        let line = self.ast.expr_span(expr).line;
+        self.step(line)?;
+        self.enter(line)?;
+        // The node is an `Rc` clone, so `self` is free to be borrowed mutably.
+        let node = self.ast.expr(expr);
+        let result = expr::accept(node.as_ref(), expr, self);
+        self.usage.depth -= 1;
+        result
+    }
+
+    fn execute(&mut self, stmt: StmtId) -> Result<(), Unwind> {
+        let line = self.ast.stmt_span(stmt).line;
+        self.step(line)?;
+        self.enter(line)?;
+        let node = self.ast.stmt(stmt);
+        let result = stmt::accept(node.as_ref(), stmt, self);
+        self.usage.depth -= 1;
+        result
+    }
+
+    // One level deeper into the tree, which is one more level of the Rust
+    // stack. Left with `usage.depth -= 1` once the node is done.
+    fn enter(&mut self, line: usize) -> Result<(), RuntimeError> {
+        if self.limits.max_depth.is_some_and(|max| self.usage.depth >= max) {
+            return Err(RuntimeError::of_kind(ErrorKind::StackOverflow, line, "Stack overflow."));
+        }
+        self.usage.depth += 1;
+        Ok(())
+    }
+
+    // Every statement and expression goes through here, so it is where
+    // running gets stopped from outside or for going over the limits.
+    fn step(&mut self, line: usize) -> Result<(), RuntimeError> {
+        if let Some(interrupt) = &self.interrupt {
+            if interrupt.swap(false, Ordering::Relaxed) {
+                return Err(RuntimeError::of_kind(ErrorKind::Interrupted, line, "Interrupted."));
+            }
+        }
+
+        self.usage.steps += 1;
+        if self.limits.max_steps.is_some_and(|max| self.usage.steps > max) {
+            return Err(RuntimeError::of_kind(ErrorKind::StepLimit, line, "Step limit exceeded."));
+        }
+        if self.usage.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Err(RuntimeError::of_kind(ErrorKind::TimeLimit, line, "Time limit exceeded."));
+        }
+        Ok(())
+    }
+
+    // Accounts for `value` having been built.
+    fn allocate(&mut self, value: &Value, line: usize) -> Result<(), RuntimeError> {
+        self.charge(heap_size(value), line)
+    }
+
+    // Accounts for `value` being kept in one more slot of a list.
+    fn store(&mut self, value: &Value, line: usize) -> Result<(), RuntimeError> {
+        self.charge(mem::size_of::<Value>() + text_size(value), line)
+    }
+
+    // Accounts for `key` and `value` being kept in one more entry of a map.
+    fn store_entry(&mut self, key: &Key, value: &Value, line: usize) -> Result<(), RuntimeError> {
+        let bytes = mem::size_of::<(Key, Value)>() + text_size(key.value()) + text_size(value);
+        self.charge(bytes, line)
+    }
+
+    fn charge(&mut self, bytes: usize, line: usize) -> Result<(), RuntimeError> {
+        self.usage.heap += bytes;
+        if self.limits.max_heap.is_some_and(|max| self.usage.heap > max) {
+            return Err(RuntimeError::of_kind(ErrorKind::HeapLimit, line, "Heap limit exceeded."));
+        }
+        Ok(())
+    }
+
+    // Calls `native`, with errors before it runs pointed at `token`.
+    fn call(&mut self, native: &NativeFunction, arguments: &[Value], token: &Token) -> Result<Value, RuntimeError> {
+        if arguments.len() != native.arity {
+            return Err(RuntimeError::new(
+                token,
+                &format!("Expected {} arguments but got {}.", native.arity, arguments.len()),
+            ));
+        }
+        if native.io && !self.limits.allow_io {
+            return Err(RuntimeError {
+                kind: ErrorKind::IoDisabled,
+                ..RuntimeError::new(token, &format!("I/O is disabled, can't call '{}'.", native.name))
+            });
+        }
+        if self.limits.max_call_depth.is_some_and(|max| self.usage.call_depth >= max) {
+            return Err(RuntimeError {
+                kind: ErrorKind::StackOverflow,
+                ..RuntimeError::new(token, "Stack overflow.")
+            });
+        }
+
+        self.usage.call_depth += 1;
+        let result = native.call(arguments);
+        self.usage.call_depth -= 1;
+        // What the native complains about is its own arguments, so it is
+        // reported as coming from inside it.
+        let value = result.map_err(|error| RuntimeError {
+            place: format!(" in {}()", native.name),
+            line: token.line,
+            ..error
+        })?;
+        self.allocate(&value, token.line)?;
+        Ok(value)
+    }
+
+    // `object.name`, bound to `object`.
+    fn method(&self, object: &Value, name: &Token) -> Result<Value, RuntimeError> {
+        let method = match object {
+            Value::List(list) => stdlib::list::method(list, &name.lexeme),
+            Value::Map(map) => stdlib::map::method(map, &name.lexeme),
+            _ => return Err(RuntimeError::new(name, "Only lists and maps have methods.")),
+        };
+        match method {
+            Some(method) => Ok(Value::Native(Rc::new(method))),
+            None => Err(RuntimeError::new(
+                name,
+                &format!("Undefined method '{}' for a {}.", name.lexeme, object.type_name()),
+            )),
+        }
+    }
+
+    // The item a `for (x in ...)` loop gets next, `None` once it is done.
+    fn next_item(&mut self, iteration: &mut Iteration, name: &Token) -> Result<Option<Value>, RuntimeError> {
+        match iteration {
+            Iteration::List(list, index) => {
+                let item = list.borrow().get(*index).cloned();
+                *index += 1;
+                Ok(item)
+            }
+            Iteration::Values(values) => Ok(values.next()),
+            Iteration::Function(function) => {
+                let function = Rc::clone(function);
+                let item = self.call(&function, &[], name)?;
+                Ok((item != Value::None).then_some(item))
+            }
+        }
+    }
+
+    fn execute_block(&mut self, statements: &[StmtId], environment: Environment) -> Result<(), Unwind> {
+        let previous = std::mem::replace(&mut self.environment, Rc::new(RefCell::new(environment)));
+        let result = statements.iter().try_for_each(|statement| self.execute(*statement));
+        self.environment = previous;
+        result
+    }
+}
+
+/// Whether `source` is a single expression with no `;`, which `eval` gives
+/// the value of.
+pub fn is_expression(source: &str) -> bool {
+    let mut scanner = Scanner::new(source.to_string());
+    let tokens = scanner.scan_tokens().to_vec();
+    scanner.errors().is_empty() && Parser::new(tokens, &mut Ast::new()).parse_expression().is_ok()
+}
+
+// Bytes `value` was just given besides the `Value` itself: its text, or the
+// slots of a list or map nothing else holds yet along with the strings copied
+// into them. A list or map that is shared was paid for when it was built, so
+// putting it inside another one only costs the slot.
+fn heap_size(value: &Value) -> usize {
+    match value {
+        Value::List(list) if Rc::strong_count(list) == 1 => {
+            list.borrow().iter().map(|item| mem::size_of::<Value>() + text_size(item)).sum()
+        }
+        Value::Map(map) if Rc::strong_count(map) == 1 => map
+            .borrow()
+            .iter()
+            .map(|(key, value)| mem::size_of::<(Key, Value)>() + text_size(key.value()) + text_size(value))
+            .sum(),
+        value => text_size(value),
+    }
+}
+
+// Bytes a copy of `value` takes besides the `Value` itself. Copying a list or
+// map only copies the reference to it.
+fn text_size(value: &Value) -> usize {
+    match value {
+        Value::String(string) => string.len(),
+        _ => 0,
+    }
+}
+
+// What a `for (x in ...)` loop goes through. Lists are read as the loop goes,
+// so it gets to items pushed while looping; the keys of a map and the
+// characters of a string are taken when it starts. A function with no
+// parameters is called for each item until it gives back nil.
+enum Iteration {
+    List(List, usize),
+    Values(std::vec::IntoIter<Value>),
+    Function(Rc<NativeFunction>),
+}
+
+impl Iteration {
+    fn new(iterable: Value, name: &Token) -> Result<Self, RuntimeError> {
+        match iterable {
+            Value::List(list) => Ok(Iteration::List(list, 0)),
+            Value::Map(map) => {
+                let keys = map.borrow().keys().map(|key| key.value().clone()).collect::<Vec<_>>();
+                Ok(Iteration::Values(keys.into_iter()))
+            }
+            Value::String(string) => {
+                let chars = string.chars().map(|char| Value::from(char.to_string())).collect::<Vec<_>>();
+                Ok(Iteration::Values(chars.into_iter()))
+            }
+            Value::Native(native) if native.arity == 0 => Ok(Iteration::Function(native)),
+            Value::Native(_) => Err(RuntimeError::new(
+                name,
+                "Can only iterate over functions that take no arguments.",
+            )),
+            _ => Err(RuntimeError::new(name, "Can only iterate over lists, maps, strings and functions.")),
+        }
+    }
+}
+
+// Where an index expression reads or writes: an item of a list or an entry
+// of a map.
+enum Place {
+    Item(List, usize),
+    Entry(Map, Key),
+}
+
+fn place(bracket: &Token, object: Value, index: &Value) -> Result<Place, RuntimeError> {
+    let error = |message: String| RuntimeError::new(bracket, &message);
+    match object {
+        Value::List(list) => {
+            let len = list.borrow().len();
+            let index = stdlib::list::index(index, len).map_err(error)?;
+            Ok(Place::Item(list, index))
+        }
+        Value::Map(map) => Ok(Place::Entry(map, stdlib::map::key(index).map_err(error)?)),
+        _ => Err(RuntimeError::new(bracket, "Only lists and maps can be indexed.")),
+    }
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::None => false,
+        Value::Boolean(boolean) => *boolean,
+        _ => true,
+    }
+}
+
+fn number_operand(operator: &Token, operand: &Value) -> Result<f64, RuntimeError> {
+    match operand {
+        Value::Number(number) => Ok(*number),
+        _ => Err(RuntimeError::new(operator, "Operand must be a number.")),
+    }
+}
+
+fn number_operands(operator: &Token, left: &Value, right: &Value) -> Result<(f64, f64), RuntimeError> {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => Ok((*left, *right)),
+        _ => Err(RuntimeError::new(operator, "Operands must be numbers.")),
+    }
+}
+
+impl<W: Write> expr::Visitor<Result<Value, RuntimeError>> for Interpreter<W> {
+    fn visit_assign_expr(&mut self, _id: ExprId, expr: &expr::Assign) -> Result<Value, RuntimeError> {
+        let value = self.evaluate(expr.value)?;
+        self.environment.borrow_mut().assign(&expr.name, value.clone())?;
+        Ok(value)
+    }
+
+    fn visit_binary_expr(&mut self, _id: ExprId, expr: &expr::Binary) -> Result<Value, RuntimeError> {
+        let left = self.evaluate(expr.left)?;
+        let right = self.evaluate(expr.right)?;
+        let operator = &expr.operator;
+
+        let value = match operator.token_type {
+            TokenType::Plus => match (left, right) {
+                (Value::Number(left), Value::Number(right)) => Value::Number(left + right),
+                (Value::String(left), Value::String(right)) => {
+                    let value = Value::String(left + &right);
+                    self.allocate(&value, operator.line)?;
+                    value
+                }
+                _ => {
+                    return Err(RuntimeError::new(
+                        operator,
+                        "Operands must be two numbers or two strings.",
+                    ))
+                }
+            },
+            TokenType::Minus => {
+                let (left, right) = number_operands(operator, &left, &right)?;
+                Value::Number(left - right)
+            }
+            TokenType::Slash => {
+                let (left, right) = number_operands(operator, &left, &right)?;
+                Value::Number(left / right)
+            }
+            TokenType::Star => {
+                let (left, right) = number_operands(operator, &left, &right)?;
+                Value::Number(left * right)
+            }
+            TokenType::Greater => {
+                let (left, right) = number_operands(operator, &left, &right)?;
+                Value::Boolean(left > right)
+            }
+            TokenType::GreaterEqual => {
+                let (left, right) = number_operands(operator, &left, &right)?;
+                Value::Boolean(left >= right)
+            }
+            TokenType::Less => {
+                let (left, right) = number_operands(operator, &left, &right)?;
+                Value::Boolean(left < right)
+            }
+            TokenType::LessEqual => {
+                let (left, right) = number_operands(operator, &left, &right)?;
+                Value::Boolean(left <= right)
+            }
+            TokenType::BangEqual => Value::Boolean(left != right),
+            TokenType::EqualEqual => Value::Boolean(left == right),
+            _ => unreachable!("{} is not a binary operator", operator.token_type),
+        };
+        Ok(value)
+    }
+
+    // A method call keeps the object it was called on, so the item `push` or
+    // `insert` puts in a list can be charged before the method runs. Going
+    // over the heap limit then leaves the list as it was.
+    fn visit_call_expr(&mut self, _id: ExprId, expr: &expr::Call) -> Result<Value, RuntimeError> {
+        let node = self.ast.expr(expr.callee);
+        let (callee, list_method) = match node.as_any().downcast_ref::<expr::Get>() {
+            Some(get) => {
+                let object = self.evaluate(get.object)?;
+                let method = self.method(&object, &get.name)?;
+                (method, matches!(object, Value::List(_)).then_some(&get.name.lexeme))
+            }
+            None => (self.evaluate(expr.callee)?, None),
+        };
+        let arguments = expr
+            .arguments
+            .iter()
+            .map(|argument| self.evaluate(*argument))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let Value::Native(native) = callee else {
+            return Err(RuntimeError::new(&expr.paren, "Can only call functions and classes."));
+        };
+        if let Some(item) = list_method.and_then(|name| stdlib::list::stored(name, &arguments)) {
+            self.store(item, expr.paren.line)?;
+        }
+        self.call(&native, &arguments, &expr.paren)
+    }
+
+    fn visit_get_expr(&mut self, _id: ExprId, expr: &expr::Get) -> Result<Value, RuntimeError> {
+        let object = self.evaluate(expr.object)?;
+        self.method(&object, &expr.name)
+    }
+
+    fn visit_grouping_expr(&mut self, _id: ExprId, expr: &expr::Grouping) -> Result<Value, RuntimeError> {
+        self.evaluate(expr.expression)
+    }
+
+    fn visit_index_expr(&mut self, _id: ExprId, expr: &expr::Index) -> Result<Value, RuntimeError> {
+        let object = self.evaluate(expr.object)?;
+        let index = self.evaluate(expr.index)?;
+        match place(&expr.bracket, object, &index)? {
+            Place::Item(list, index) => Ok(list.borrow()[index].clone()),
+            Place::Entry(map, key) => map
+                .borrow()
+                .get(&key)
+                .cloned()
+                .ok_or_else(|| RuntimeError::new(&expr.bracket, &stdlib::map::missing(&key))),
+        }
+    }
+
+    fn visit_index_set_expr(&mut self, _id: ExprId, expr: &expr::IndexSet) -> Result<Value, RuntimeError> {
+        let object = self.evaluate(expr.object)?;
+        let index = self.evaluate(expr.index)?;
+        let value = self.evaluate(expr.value)?;
+        // Charged before storing, so going over the limit leaves the list or
+        // map as it was.
+        match place(&expr.bracket, object, &index)? {
+            Place::Item(list, index) => {
+                self.charge(text_size(&value), expr.bracket.line)?;
+                list.borrow_mut()[index] = value.clone();
+            }
+            Place::Entry(map, key) => {
+                if map.borrow().contains_key(&key) {
+                    self.charge(text_size(&value), expr.bracket.line)?;
+                } else {
+                    self.store_entry(&key, &value, expr.bracket.line)?;
+                }
+                map.borrow_mut().insert(key, value.clone());
+            }
+        }
+        Ok(value)
+    }
+
+    fn visit_list_expr(&mut self, _id: ExprId, expr: &expr::List) -> Result<Value, RuntimeError> {
+        let elements = expr
+            .elements
+            .iter()
+            .map(|element| self.evaluate(*element))
+            .collect::<Result<Vec<_>, _>>()?;
+        let list = Value::list(elements);
+        self.allocate(&list, expr.bracket.line)?;
+        Ok(list)
+    }
+
+    fn visit_literal_expr(&mut self, _id: ExprId, expr: &expr::Literal) -> Result<Value, RuntimeError> {
+        Ok(expr.value.clone())
+    }
+
+    // Entries are evaluated in order, key then value. A key written twice
+    // keeps its first place and its last value.
+    fn visit_map_expr(&mut self, _id: ExprId, expr: &expr::Map) -> Result<Value, RuntimeError> {
+        let mut entries = vec![];
+        for (key, value) in expr.keys.iter().zip(&expr.values) {
+            let key = self.evaluate(*key)?;
+            let key = stdlib::map::key(&key).map_err(|message| RuntimeError::new(&expr.brace, &message))?;
+            entries.push((key, self.evaluate(*value)?));
+        }
+        let map = Value::map(entries);
+        self.allocate(&map, expr.brace.line)?;
+        Ok(map)
+    }
+
+    fn visit_logical_expr(&mut self, _id: ExprId, expr: &expr::Logical) -> Result<Value, RuntimeError> {
+        let left = self.evaluate(expr.left)?;
+
+        // Short-circuits, giving back the operand itself rather than a bool.
+        if expr.operator.token_type == TokenType::Or {
+            if is_truthy(&left) {
+                return Ok(left);
+            }
+        } else if !is_truthy(&left) {
+            return Ok(left);
+        }
+        self.evaluate(expr.right)
+    }
+
+    fn visit_unary_expr(&mut self, _id: ExprId, expr: &expr::Unary) -> Result<Value, RuntimeError> {
+        let right = self.evaluate(expr.right)?;
+
+        match expr.operator.token_type {
+            TokenType::Minus => Ok(Value::Number(-number_operand(&expr.operator, &right)?)),
+            TokenType::Bang => Ok(Value::Boolean(!is_truthy(&right))),
+            _ => unreachable!("{} is not a unary operator", expr.operator.token_type),
+        }
+    }
+
+    fn visit_variable_expr(&mut self, _id: ExprId, expr: &expr::Variable) -> Result<Value, RuntimeError> {
+        self.environment.borrow().get(&expr.name)
+    }
+}
+
+impl<W: Write> stmt::Visitor<Result<(), Unwind>> for Interpreter<W> {
+    fn visit_block_stmt(&mut self, _id: StmtId, stmt: &stmt::Block) -> Result<(), Unwind> {
+        let environment = Environment::with_enclosing(Rc::clone(&self.environment));
+        self.execute_block(&stmt.statements, environment)
+    }
+
+    fn visit_break_stmt(&mut self, _id: StmtId, _stmt: &stmt::Break) -> Result<(), Unwind> {
+        Err(Unwind::Break)
+    }
+
+    fn visit_continue_stmt(&mut self, _id: StmtId, _stmt: &stmt::Continue) -> Result<(), Unwind> {
+        Err(Unwind::Continue)
+    }
+
+    fn visit_expression_stmt(&mut self, _id: StmtId, stmt: &stmt::Expression) -> Result<(), Unwind> {
+        self.evaluate(stmt.expression)?;
+        Ok(())
+    }
+
+    fn visit_for_stmt(&mut self, id: StmtId, _stmt: &stmt::For) -> Result<(), Unwind> {
+        let line = self.ast.stmt_span(id).line;
+        Err(Unwind::Error(RuntimeError::at_line(line, "For loops must go through Desugar before running.")))
+    }
+
+    // Each item gets a scope of its own, with the loop variable in it.
+    fn visit_for_in_stmt(&mut self, _id: StmtId, stmt: &stmt::ForIn) -> Result<(), Unwind> {
+        let iterable = self.evaluate(stmt.iterable)?;
+        let mut iteration = Iteration::new(iterable, &stmt.name)?;
+        while let Some(item) = self.next_item(&mut iteration, &stmt.name)? {
+            let mut environment = Environment::with_enclosing(Rc::clone(&self.environment));
+            environment.define(&stmt.name.lexeme, item);
+            match self.execute_block(&[stmt.body], environment) {
+                Ok(()) | Err(Unwind::Continue) => {}
+                Err(Unwind::Break) => break,
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_if_stmt(&mut self, _id: StmtId, stmt: &stmt::If) -> Result<(), Unwind> {
+        if is_truthy(&self.evaluate(stmt.condition)?) {
+            self.execute(stmt.then_branch)
+        } else if let Some(else_branch) = stmt.else_branch {
+            self.execute(else_branch)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn visit_print_stmt(&mut self, id: StmtId, stmt: &stmt::Print) -> Result<(), Unwind> {
+        let value = self.evaluate(stmt.expression)?;
+        writeln!(self.output, "{value}").map_err(|error| {
+            RuntimeError::at_line(self.ast.stmt_span(id).line, &format!("Can't write the output: {error}.")).into()
+        })
+    }
+
+    fn visit_var_stmt(&mut self, _id: StmtId, stmt: &stmt::Var) -> Result<(), Unwind> {
+        let value = match stmt.initializer {
+            Some(initializer) => self.evaluate(initializer)?,
+            None => Value::None,
+        };
+        self.environment.borrow_mut().define(&stmt.name.lexeme, value);
+        Ok(())
+    }
+
+    fn visit_while_stmt(&mut self, _id: StmtId, stmt: &stmt::While) -> Result<(), Unwind> {
+        while is_truthy(&self.evaluate(stmt.condition)?) {
+            match self.execute(stmt.body) {
+                Ok(()) | Err(Unwind::Continue) => {}
+                Err(Unwind::Break) => break,
+                Err(error) => return Err(error),
+            }
+            if let Some(increment) = stmt.increment {
+                self.evaluate(increment)?;
+            }
+        }
+        Ok(())
+    }
+}
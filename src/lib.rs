@@ -0,0 +1,64 @@
+//! Lox, from *Crafting Interpreters*, as a library: the `Scanner`, the
+//! `Parser` and the syntax tree it builds, and a tree-walking `Interpreter`
+//! that can be embedded to run scripts.
+//!
+//! ```
+//! use rlox::{Interpreter, Value};
+//!
+//! let mut interpreter = Interpreter::new();
+//! interpreter.eval("var answer = 6 * 7;").unwrap();
+//! assert_eq!(interpreter.eval("answer").unwrap(), Value::Number(42.0));
+//! ```
+
+pub mod ast;
+// Generated by build.rs from lox.ast; not every pass uses all of their API.
+#[allow(dead_code)]
+pub mod expr {
+    include!(concat!(env!("OUT_DIR"), "/expr.rs"));
+}
+#[allow(dead_code)]
+pub mod stmt {
+    include!(concat!(env!("OUT_DIR"), "/stmt.rs"));
+}
+pub mod ast_printer;
+pub mod desugar;
+mod environment;
+mod error;
+pub mod formatter;
+mod interpreter;
+mod limits;
+mod native;
+mod parser;
+pub mod resolver;
+#[cfg(test)]
+mod round_trip;
+pub mod rpn_printer;
+mod scanner;
+mod stdlib;
+mod token;
+
+use ast::{Ast, StmtId};
+
+pub use error::{Error, SyntaxError};
+pub use interpreter::{is_expression, ErrorKind, Interpreter, RuntimeError};
+pub use limits::{Limits, DEFAULT_MAX_CALL_DEPTH, DEFAULT_MAX_DEPTH, MAX_HEIGHT, MAX_NESTING};
+pub use native::{argument, wrong_argument, FromValue, NativeFunction};
+pub use parser::Parser;
+pub use scanner::{load_keywords, Scanner};
+pub use token::{Entries, Items, Key, Token, TokenType, Value};
+
+/// Scans and parses `source` into `ast`, then lowers it with `Desugar` and
+/// checks it with the `Resolver`, which is what the `Interpreter` expects to
+/// run.
+pub fn parse(source: &str, ast: &mut Ast) -> Result<Vec<StmtId>, Vec<SyntaxError>> {
+    let mut scanner = Scanner::new(source.to_string());
+    let tokens = scanner.scan_tokens().to_vec();
+    if !scanner.errors().is_empty() {
+        return Err(scanner.errors().to_vec());
+    }
+
+    let statements = Parser::new(tokens, ast).parse()?;
+    let statements = desugar::Desugar::new().run(ast, &statements);
+    resolver::Resolver::new(ast).run(&statements)?;
+    Ok(statements)
+}
@@ -0,0 +1,78 @@
+use std::time::{Duration, Instant};
+
+/// How deeply brackets, blocks, statement bodies and unary operators can
+/// nest in the source. The parser recurses on each, up to some 17 KiB of
+/// stack per parenthesis in a debug build, so this keeps it well within a
+/// 2 MiB thread.
+pub const MAX_NESTING: usize = 64;
+
+/// How tall the tree for an expression can get. Chains like `a + b + c` grow
+/// it without nesting, and every pass over the tree is recursive. This
+/// limits what scripts can write: a plain chain of more than about 256 `+`
+/// terms, or of chained calls like `f()()()`, is a syntax error.
+pub const MAX_HEIGHT: usize = 256;
+
+/// Call depth allowed unless set otherwise, well below what overflows the
+/// Rust stack even in debug builds.
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 256;
+
+/// Nesting of the statements and expressions being run allowed unless set
+/// otherwise. Parsed code stays below it, since the parser stops at
+/// `MAX_NESTING` and `MAX_HEIGHT`; it is there for trees built by hand
+/// through `ast_mut`.
+pub const DEFAULT_MAX_DEPTH: usize = 1024;
+
+/// Bounds on what a single run can use, for scripts that can't be trusted.
+/// `None` means no limit. Going over one stops the run with a
+/// `RuntimeError` of its own `ErrorKind`.
+#[derive(Debug, Clone)]
+pub struct Limits {
+    /// Statements executed plus expressions evaluated.
+    pub max_steps: Option<u64>,
+    /// Calls in progress at once. Deep recursion would otherwise overflow
+    /// the Rust stack and abort, so this one is set by default.
+    pub max_call_depth: Option<usize>,
+    /// Statements and expressions being run at once, one inside the other.
+    /// Running is recursive too, so like calls this is set by default.
+    pub max_depth: Option<usize>,
+    /// Bytes of the values built while running, like strings from `+` or
+    /// what natives return, and of what gets stored in lists and maps. It is
+    /// what gets allocated, never given back.
+    pub max_heap: Option<usize>,
+    pub timeout: Option<Duration>,
+    /// Whether the natives defined with `define_io_native`, like `readFile`
+    /// or `getenv`, can be called. Off unless the host turns it on.
+    pub allow_io: bool,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_steps: None,
+            max_call_depth: Some(DEFAULT_MAX_CALL_DEPTH),
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+            max_heap: None,
+            timeout: None,
+            allow_io: false,
+        }
+    }
+}
+
+/// What the current run has used so far.
+#[derive(Default)]
+pub(crate) struct Usage {
+    pub steps: u64,
+    pub call_depth: usize,
+    pub depth: usize,
+    pub heap: usize,
+    pub deadline: Option<Instant>,
+}
+
+impl Usage {
+    pub fn starting_now(limits: &Limits) -> Self {
+        Usage {
+            deadline: limits.timeout.map(|timeout| Instant::now() + timeout),
+            ..Usage::default()
+        }
+    }
+}
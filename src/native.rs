@@ -0,0 +1,178 @@
+use std::fmt;
+
+use crate::{RuntimeError, Value};
+
+type Function = Box<dyn Fn(&[Value]) -> Result<Value, RuntimeError>>;
+
+/// Function written in Rust that scripts can call. Hosts register them with
+/// `Interpreter::define_native`.
+pub struct NativeFunction {
+    pub name: String,
+    pub arity: usize,
+    /// Reaches outside the interpreter, see `Limits::allow_io`.
+    pub io: bool,
+    function: Function,
+}
+
+impl NativeFunction {
+    pub fn new<F>(name: &str, arity: usize, function: F) -> Self
+    where
+        F: Fn(&[Value]) -> Result<Value, RuntimeError> + 'static,
+    {
+        NativeFunction {
+            name: name.to_string(),
+            arity,
+            io: false,
+            function: Box::new(function),
+        }
+    }
+
+    /// Runs the function. The interpreter has checked the arity by then.
+    pub fn call(&self, arguments: &[Value]) -> Result<Value, RuntimeError> {
+        (self.function)(arguments)
+    }
+}
+
+impl fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<native fn {}>", self.name)
+    }
+}
+
+// A native is only equal to itself, like functions in Lox.
+impl PartialEq for NativeFunction {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+/// Rust types the arguments of a native can be turned into, see `argument`.
+pub trait FromValue: Sized {
+    /// What a value of the type is called in error messages, like "a number".
+    fn expected() -> String;
+
+    fn from_value(value: &Value) -> Option<Self>;
+}
+
+impl FromValue for f64 {
+    fn expected() -> String {
+        "a number".to_string()
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+}
+
+/// For counts and indices.
+impl FromValue for usize {
+    fn expected() -> String {
+        "a whole number, not negative".to_string()
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Number(number) if *number >= 0.0 && number.fract() == 0.0 => Some(*number as usize),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for String {
+    fn expected() -> String {
+        "a string".to_string()
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(string) => Some(string.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for bool {
+    fn expected() -> String {
+        "a boolean".to_string()
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Boolean(boolean) => Some(*boolean),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for Value {
+    fn expected() -> String {
+        "a value".to_string()
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        Some(value.clone())
+    }
+}
+
+/// `nil` is `None`, anything else has to convert to `T`.
+impl<T: FromValue> FromValue for Option<T> {
+    fn expected() -> String {
+        format!("{} or nil", T::expected())
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::None => Some(None),
+            value => T::from_value(value).map(Some),
+        }
+    }
+}
+
+/// Argument `index` of a native call as a `T`, or an error saying what was
+/// expected there.
+pub fn argument<T: FromValue>(arguments: &[Value], index: usize) -> Result<T, RuntimeError> {
+    arguments
+        .get(index)
+        .and_then(T::from_value)
+        .ok_or_else(|| wrong_argument(index, &T::expected()))
+}
+
+/// The error for argument `index` not being what the native takes, worded
+/// the same for all of them.
+pub fn wrong_argument(index: usize, expected: &str) -> RuntimeError {
+    RuntimeError::native(&format!("Argument {} must be {expected}.", index + 1))
+}
+
+impl From<f64> for Value {
+    fn from(number: f64) -> Self {
+        Value::Number(number)
+    }
+}
+
+impl From<String> for Value {
+    fn from(string: String) -> Self {
+        Value::String(string)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(string: &str) -> Self {
+        Value::String(string.to_string())
+    }
+}
+
+impl From<bool> for Value {
+    fn from(boolean: bool) -> Self {
+        Value::Boolean(boolean)
+    }
+}
+
+/// `None` becomes `nil`.
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(option: Option<T>) -> Self {
+        option.map_or(Value::None, Into::into)
+    }
+}
@@ -0,0 +1,572 @@
+use std::collections::HashMap;
+
+use crate::ast::{Ast, ExprId, Span, StmtId};
+use crate::expr::{self, Expr};
+use crate::limits::{MAX_HEIGHT, MAX_NESTING};
+use crate::stmt;
+use crate::{SyntaxError, Token, TokenType, Value};
+
+/*
+
+program     →  declaration* EOF ;
+declaration →  varDecl
+                    | statement ;
+varDecl     →  "var" IDENTIFIER ( "=" expression )? ";" ;
+statement   →  exprStmt
+                    | breakStmt
+                    | continueStmt
+                    | forStmt
+                    | ifStmt
+                    | printStmt
+                    | whileStmt
+                    | block ;
+exprStmt    →  expression ";" ;
+breakStmt   →  "break" ";" ;
+continueStmt → "continue" ";" ;
+forStmt     →  "for" "(" ( varDecl | exprStmt | ";" )
+                    expression? ";"
+                    expression? ")" statement
+                    | "for" "(" IDENTIFIER "in" expression ")" statement ;
+ifStmt      →  "if" "(" expression ")" statement
+                    ( "else" statement )? ;
+printStmt   →  "print" expression ";" ;
+whileStmt   →  "while" "(" expression ")" statement ;
+block       →  "{" declaration* "}" ;
+
+expression  →  assignment ;
+assignment  →  ( IDENTIFIER | call "[" expression "]" ) "=" assignment
+                    | logic_or ;
+logic_or    →  logic_and ( "or" logic_and )* ;
+logic_and   →  equality ( "and" equality )* ;
+equality    →  comparison ( ( "!=" | "==" ) comparison )* ;
+comparison  →  term ( ( ">" | ">=" | "<" | "<=" ) term )* ;
+term        →  factor ( ( "-" | "+" ) factor )* ;
+factor      →  unary ( ( "/" | "*" ) unary )* ;
+unary       →  ( "!" | "-" ) unary
+                    | call ;
+call        →  primary ( "(" arguments? ")" | "[" expression "]"
+                    | "." IDENTIFIER )* ;
+arguments   →  expression ( "," expression )* ;
+primary     →  NUMBER | STRING | "true" | "false" | "nil"
+                    | "(" expression ")" | "[" arguments? "]"
+                    | "{" ( entry ( "," entry )* )? "}" | IDENTIFIER ;
+entry       →  expression ":" expression ;
+
+A "{" that starts a statement opens a block, so an expression statement can't
+begin with a map literal unless it is wrapped in parentheses.
+*/
+
+/// Builds the tree for a list of tokens into an `Ast`. Input nested deeper
+/// than `MAX_NESTING`, or an expression whose tree is taller than
+/// `MAX_HEIGHT`, is a syntax error, so neither the parser nor the passes that
+/// walk the tree recursively can overflow the stack. The height limit also
+/// applies to chains: `1 + 1 + ...` with more than about 256 terms doesn't
+/// parse.
+pub struct Parser<'a> {
+    tokens: Vec<Token>,
+    current: usize,
+    ast: &'a mut Ast,
+    // Calls to `nested` in progress.
+    depth: usize,
+    // How tall the tree under each expression is.
+    heights: HashMap<ExprId, usize>,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(tokens: Vec<Token>, ast: &'a mut Ast) -> Self {
+        Self {tokens, current: 0, ast, depth: 0, heights: HashMap::new()}
+    }
+
+    fn equals(&mut self, tok_types: &[TokenType]) -> bool {
+        for tok_type in tok_types {
+            if self.check(tok_type) {
+                self.advance();
+                return true;
+            }
+        }
+        false
+    }
+
+    fn check(&self, tok_type: &TokenType) -> bool {
+        if self.is_at_end() { return false; }
+
+        self.peek().token_type == *tok_type
+    }
+
+    fn advance(&mut self) -> Token {
+        if !self.is_at_end() { self.current += 1 }
+        self.previous()
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.peek().token_type == TokenType::EOF
+    }
+
+    fn peek(&self) -> Token {
+        self.tokens.get(self.current).expect("Expects a token").clone()
+    }
+
+    fn peek_next(&self) -> Option<&Token> {
+        self.tokens.get(self.current + 1)
+    }
+
+    fn previous(&self) -> Token {
+        self.tokens.get(self.current - 1).expect("Expects a token").clone()
+    }
+
+    // Span from `start` up to the last token consumed.
+    fn span_from(&self, start: &Token) -> Span {
+        Span {
+            start: start.start,
+            end: self.previous().span().end,
+            line: start.line,
+        }
+    }
+
+    fn error(&self, token: Token, message: &str) -> SyntaxError {
+        SyntaxError::at(&token, message)
+    }
+
+    // Runs `parse` one level deeper. Every rule that can recurse goes through
+    // here, so the parser's own stack stays bounded.
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T, SyntaxError>) -> Result<T, SyntaxError> {
+        if self.depth >= MAX_NESTING {
+            return Err(self.error(self.peek(), "Too deeply nested."));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    // Chains like `a + b + c` or `f()()` make the tree taller without
+    // recursing, so the height of what gets built is checked too.
+    fn alloc_expr(&mut self, expr: impl Expr + 'static, start: &Token) -> Result<ExprId, SyntaxError> {
+        let height = 1 + expr
+            .children()
+            .iter()
+            .map(|child| self.heights.get(child).copied().unwrap_or(0))
+            .max()
+            .unwrap_or(0);
+        if height > MAX_HEIGHT {
+            return Err(self.error(self.previous(), "Too deeply nested."));
+        }
+        let id = self.ast.alloc_expr(expr, self.span_from(start));
+        self.heights.insert(id, height);
+        Ok(id)
+    }
+
+    fn consume(&mut self, tok_type: TokenType, message: &str) -> Result<Token, SyntaxError> {
+        if self.check(&tok_type) {
+            return Ok(self.advance());
+        }
+        Err(self.error(self.peek(), message))
+    }
+
+    fn synchronize(&mut self) {
+        self.advance();
+
+        while !self.is_at_end() {
+            if self.previous().token_type == TokenType::Semicolon {
+                return;
+            }
+
+            match self.peek().token_type {
+                TokenType::Class | TokenType::Fun | TokenType::Var | TokenType::For | TokenType::If | TokenType::While | TokenType::Print | TokenType::Return => return,
+                _ => (),
+            }
+
+            self.advance();
+        }
+    } 
+
+    fn primary(&mut self) -> Result<ExprId, SyntaxError> {
+        let start = self.peek();
+        if self.equals(&[TokenType::False]) { 
+            return self.alloc_expr(expr::Literal::new(Value::Boolean(false)), &start);
+        }
+        
+        if self.equals(&[TokenType::True]) { 
+            return self.alloc_expr(expr::Literal::new(Value::Boolean(true)), &start);
+        }
+        
+        if self.equals(&[TokenType::Nil]) { 
+            return self.alloc_expr(expr::Literal::new(Value::None), &start);
+        }
+
+        if self.equals(&[TokenType::Number, TokenType::String]) {
+            return self.alloc_expr(expr::Literal::new(self.previous().literal), &start);
+        }
+
+        if self.equals(&[TokenType::Identifier]) {
+            return self.alloc_expr(expr::Variable::new(self.previous()), &start);
+        }
+
+        if self.equals(&[TokenType::LeftParen]) { 
+            let expr = self.expression()?;
+            self.consume(TokenType::RightParen, "Expect ')' after expression.")?;
+
+            return self.alloc_expr(expr::Grouping::new(expr), &start);
+        }
+
+        if self.equals(&[TokenType::LeftBracket]) {
+            let bracket = self.previous();
+            let elements = self.arguments(TokenType::RightBracket, "elements")?;
+            self.consume(TokenType::RightBracket, "Expect ']' after list elements.")?;
+            return self.alloc_expr(expr::List::new(bracket, elements), &start);
+        }
+
+        if self.equals(&[TokenType::LeftBrace]) {
+            let brace = self.previous();
+            let (mut keys, mut values) = (vec![], vec![]);
+            if !self.check(&TokenType::RightBrace) {
+                loop {
+                    if keys.len() >= 255 {
+                        return Err(self.error(self.peek(), "Can't have more than 255 entries."));
+                    }
+                    keys.push(self.expression()?);
+                    self.consume(TokenType::Colon, "Expect ':' after map key.")?;
+                    values.push(self.expression()?);
+                    if !self.equals(&[TokenType::Comma]) {
+                        break;
+                    }
+                }
+            }
+            self.consume(TokenType::RightBrace, "Expect '}' after map entries.")?;
+            return self.alloc_expr(expr::Map::new(brace, keys, values), &start);
+        }
+
+        Err(self.error(self.peek(), "Expect expression."))
+    }
+
+    fn unary(&mut self) -> Result<ExprId, SyntaxError> {
+        let start = self.peek();
+        if self.equals(&[TokenType::Bang, TokenType::Minus]) {
+            let operator = self.previous();
+            let right = self.nested(Self::unary)?;
+            return self.alloc_expr(expr::Unary::new(operator, right), &start);
+        }
+
+        self.call()
+    }
+
+    fn call(&mut self) -> Result<ExprId, SyntaxError> {
+        let start = self.peek();
+        let mut expr = self.primary()?;
+
+        loop {
+            if self.equals(&[TokenType::LeftParen]) {
+                let arguments = self.arguments(TokenType::RightParen, "arguments")?;
+                let paren = self.consume(TokenType::RightParen, "Expect ')' after arguments.")?;
+                expr = self.alloc_expr(expr::Call::new(expr, paren, arguments), &start)?;
+            } else if self.equals(&[TokenType::LeftBracket]) {
+                let bracket = self.previous();
+                let index = self.expression()?;
+                self.consume(TokenType::RightBracket, "Expect ']' after index.")?;
+                expr = self.alloc_expr(expr::Index::new(expr, bracket, index), &start)?;
+            } else if self.equals(&[TokenType::Dot]) {
+                let name = self.consume(TokenType::Identifier, "Expect property name after '.'.")?;
+                expr = self.alloc_expr(expr::Get::new(expr, name), &start)?;
+            } else {
+                break;
+            }
+        }
+
+        Ok(expr)
+    }
+
+    // Comma separated expressions up to `end`, which is left for the caller
+    // to consume. `what` names them in the error for having too many.
+    fn arguments(&mut self, end: TokenType, what: &str) -> Result<Vec<ExprId>, SyntaxError> {
+        let mut arguments = vec![];
+        if !self.check(&end) {
+            loop {
+                if arguments.len() >= 255 {
+                    return Err(self.error(self.peek(), &format!("Can't have more than 255 {what}.")));
+                }
+                arguments.push(self.expression()?);
+                if !self.equals(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        Ok(arguments)
+    }
+
+    fn factor(&mut self) -> Result<ExprId, SyntaxError> {
+        let start = self.peek();
+        let mut expr = self.unary()?;
+
+        while self.equals(&[TokenType::Slash, TokenType::Star]) {
+            let operator = self.previous();
+            let right = self.unary()?;
+            expr = self.alloc_expr(expr::Binary::new(expr, operator, right), &start)?;
+        }
+        Ok(expr)
+    }
+
+    fn term(&mut self) -> Result<ExprId, SyntaxError> {
+        let start = self.peek();
+        let mut expr = self.factor()?;
+
+        while self.equals(&[TokenType::Minus, TokenType::Plus]) {
+            let operator = self.previous();
+            let right = self.factor()?;
+            expr = self.alloc_expr(expr::Binary::new(expr, operator, right), &start)?;
+        }
+        Ok(expr)
+    }
+
+    fn comparison(&mut self) -> Result<ExprId, SyntaxError> {
+        let start = self.peek();
+        let mut expr = self.term()?;
+
+        while self.equals(&[TokenType::Greater, TokenType::GreaterEqual, TokenType::Less, TokenType::LessEqual]) {
+            let operator = self.previous();
+            let right = self.term()?;
+            expr = self.alloc_expr(expr::Binary::new(expr, operator, right), &start)?;
+        }
+        Ok(expr)
+    }
+
+    fn expression(&mut self) -> Result<ExprId, SyntaxError> {
+        self.nested(Self::assignment)
+    }
+
+    fn assignment(&mut self) -> Result<ExprId, SyntaxError> {
+        let start = self.peek();
+        let expr = self.or()?;
+
+        if self.equals(&[TokenType::Equal]) {
+            let equals = self.previous();
+            let value = self.nested(Self::assignment)?;
+
+            let target = self.ast.expr(expr);
+            if let Some(variable) = target.as_any().downcast_ref::<expr::Variable>() {
+                return self.alloc_expr(expr::Assign::new(variable.name.clone(), value), &start);
+            }
+            if let Some(index) = target.as_any().downcast_ref::<expr::Index>() {
+                let set = expr::IndexSet::new(index.object, index.bracket.clone(), index.index, value);
+                return self.alloc_expr(set, &start);
+            }
+
+            return Err(self.error(equals, "Invalid assignment target."));
+        }
+        Ok(expr)
+    }
+
+    fn or(&mut self) -> Result<ExprId, SyntaxError> {
+        let start = self.peek();
+        let mut expr = self.and()?;
+
+        while self.equals(&[TokenType::Or]) {
+            let operator = self.previous();
+            let right = self.and()?;
+            expr = self.alloc_expr(expr::Logical::new(expr, operator, right), &start)?;
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<ExprId, SyntaxError> {
+        let start = self.peek();
+        let mut expr = self.equality()?;
+
+        while self.equals(&[TokenType::And]) {
+            let operator = self.previous();
+            let right = self.equality()?;
+            expr = self.alloc_expr(expr::Logical::new(expr, operator, right), &start)?;
+        }
+        Ok(expr)
+    }
+
+    fn equality(&mut self) -> Result<ExprId, SyntaxError> {
+        let start = self.peek();
+        let mut expr = self.comparison()?;
+        while self.equals(&[TokenType::BangEqual, TokenType::EqualEqual]) {
+            let operator = self.previous();
+            let right = self.comparison()?;
+            expr = self.alloc_expr(expr::Binary::new(expr, operator, right), &start)?;
+        }
+        Ok(expr)
+    }
+
+    fn declaration(&mut self) -> Result<StmtId, SyntaxError> {
+        if self.equals(&[TokenType::Var]) {
+            return self.var_declaration();
+        }
+        self.statement()
+    }
+
+    fn var_declaration(&mut self) -> Result<StmtId, SyntaxError> {
+        let start = self.previous();
+        let name = self.consume(TokenType::Identifier, "Expect variable name.")?;
+
+        let initializer = if self.equals(&[TokenType::Equal]) {
+            Some(self.expression()?)
+        } else {
+            None
+        };
+
+        self.consume(TokenType::Semicolon, "Expect ';' after variable declaration.")?;
+        Ok(self.ast.alloc_stmt(stmt::Var::new(name, initializer), self.span_from(&start)))
+    }
+
+    fn statement(&mut self) -> Result<StmtId, SyntaxError> {
+        if self.equals(&[TokenType::Break, TokenType::Continue]) {
+            return self.jump_statement();
+        }
+        if self.equals(&[TokenType::For]) {
+            return self.for_statement();
+        }
+        if self.equals(&[TokenType::If]) {
+            return self.if_statement();
+        }
+        if self.equals(&[TokenType::Print]) {
+            return self.print_statement();
+        }
+        if self.equals(&[TokenType::While]) {
+            return self.while_statement();
+        }
+        if self.equals(&[TokenType::LeftBrace]) {
+            let start = self.previous();
+            let statements = self.block()?;
+            return Ok(self.ast.alloc_stmt(stmt::Block::new(statements), self.span_from(&start)));
+        }
+        self.expression_statement()
+    }
+
+    // Whether there is a loop around is for the `Resolver` to check.
+    fn jump_statement(&mut self) -> Result<StmtId, SyntaxError> {
+        let keyword = self.previous();
+        self.consume(TokenType::Semicolon, &format!("Expect ';' after '{}'.", keyword.lexeme))?;
+        let span = self.span_from(&keyword);
+        if keyword.token_type == TokenType::Break {
+            Ok(self.ast.alloc_stmt(stmt::Break::new(keyword), span))
+        } else {
+            Ok(self.ast.alloc_stmt(stmt::Continue::new(keyword), span))
+        }
+    }
+
+    // `for (x in ...)` is told apart from a C-style loop by the `in` right
+    // after the name.
+    fn for_statement(&mut self) -> Result<StmtId, SyntaxError> {
+        let start = self.previous();
+        self.consume(TokenType::LeftParen, "Expect '(' after 'for'.")?;
+
+        if self.check(&TokenType::Identifier) && self.peek_next().is_some_and(|next| next.token_type == TokenType::In) {
+            let name = self.advance();
+            self.advance();
+            let iterable = self.expression()?;
+            self.consume(TokenType::RightParen, "Expect ')' after for-in clause.")?;
+            let body = self.nested(Self::statement)?;
+            return Ok(self.ast.alloc_stmt(stmt::ForIn::new(name, iterable, body), self.span_from(&start)));
+        }
+
+        let initializer = if self.equals(&[TokenType::Semicolon]) {
+            None
+        } else if self.equals(&[TokenType::Var]) {
+            Some(self.var_declaration()?)
+        } else {
+            Some(self.expression_statement()?)
+        };
+
+        let condition = if self.check(&TokenType::Semicolon) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+        self.consume(TokenType::Semicolon, "Expect ';' after loop condition.")?;
+
+        let increment = if self.check(&TokenType::RightParen) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+        self.consume(TokenType::RightParen, "Expect ')' after for clauses.")?;
+
+        let body = self.nested(Self::statement)?;
+        Ok(self.ast.alloc_stmt(stmt::For::new(initializer, condition, increment, body), self.span_from(&start)))
+    }
+
+    fn if_statement(&mut self) -> Result<StmtId, SyntaxError> {
+        let start = self.previous();
+        self.consume(TokenType::LeftParen, "Expect '(' after 'if'.")?;
+        let condition = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after if condition.")?;
+
+        let then_branch = self.nested(Self::statement)?;
+        let else_branch = if self.equals(&[TokenType::Else]) {
+            Some(self.nested(Self::statement)?)
+        } else {
+            None
+        };
+
+        Ok(self.ast.alloc_stmt(stmt::If::new(condition, then_branch, else_branch), self.span_from(&start)))
+    }
+
+    fn print_statement(&mut self) -> Result<StmtId, SyntaxError> {
+        let start = self.previous();
+        let value = self.expression()?;
+        self.consume(TokenType::Semicolon, "Expect ';' after value.")?;
+        Ok(self.ast.alloc_stmt(stmt::Print::new(value), self.span_from(&start)))
+    }
+
+    fn while_statement(&mut self) -> Result<StmtId, SyntaxError> {
+        let start = self.previous();
+        self.consume(TokenType::LeftParen, "Expect '(' after 'while'.")?;
+        let condition = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after condition.")?;
+        let body = self.nested(Self::statement)?;
+
+        Ok(self.ast.alloc_stmt(stmt::While::new(condition, body, None), self.span_from(&start)))
+    }
+
+    fn block(&mut self) -> Result<Vec<StmtId>, SyntaxError> {
+        let mut statements = vec![];
+
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            statements.push(self.nested(Self::declaration)?);
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after block.")?;
+        Ok(statements)
+    }
+
+    fn expression_statement(&mut self) -> Result<StmtId, SyntaxError> {
+        let start = self.peek();
+        let expr = self.expression()?;
+        self.consume(TokenType::Semicolon, "Expect ';' after expression.")?;
+        Ok(self.ast.alloc_stmt(stmt::Expression::new(expr), self.span_from(&start)))
+    }
+
+    // After an error the parser skips to the next statement, so a single run
+    // finds all of them.
+    pub fn parse(&mut self) -> Result<Vec<StmtId>, Vec<SyntaxError>> {
+        let mut statements = vec![];
+        let mut errors = vec![];
+        while !self.is_at_end() {
+            match self.declaration() {
+                Ok(statement) => statements.push(statement),
+                Err(error) => {
+                    errors.push(error);
+                    self.synchronize();
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(statements)
+    }
+
+    /// Parses the whole input as a single expression with no `;`, which is
+    /// what the REPL echoes.
+    pub fn parse_expression(&mut self) -> Result<ExprId, SyntaxError> {
+        let expr = self.expression()?;
+        if !self.is_at_end() {
+            return Err(self.error(self.peek(), "Expect end of expression."));
+        }
+        Ok(expr)
+    }
+}
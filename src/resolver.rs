@@ -0,0 +1,97 @@
+use crate::ast::{Ast, StmtId};
+use crate::stmt;
+use crate::{SyntaxError, Token};
+
+/// Static checks that need to know where a statement is, run on the tree
+/// after `Desugar`. For now that is `break` and `continue` only being used
+/// inside a loop.
+pub struct Resolver<'a> {
+    ast: &'a Ast,
+    loops: usize,
+    errors: Vec<SyntaxError>,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(ast: &'a Ast) -> Self {
+        Resolver {
+            ast,
+            loops: 0,
+            errors: vec![],
+        }
+    }
+
+    /// Every error found in `statements`, not just the first.
+    pub fn run(&mut self, statements: &[StmtId]) -> Result<(), Vec<SyntaxError>> {
+        for statement in statements {
+            self.stmt(*statement);
+        }
+        if !self.errors.is_empty() {
+            return Err(std::mem::take(&mut self.errors));
+        }
+        Ok(())
+    }
+
+    fn stmt(&mut self, stmt: StmtId) {
+        self.ast.accept_stmt(stmt, self);
+    }
+
+    fn loop_body(&mut self, body: StmtId) {
+        self.loops += 1;
+        self.stmt(body);
+        self.loops -= 1;
+    }
+
+    fn jump(&mut self, keyword: &Token) {
+        if self.loops == 0 {
+            let message = format!("Can't use '{}' outside of a loop.", keyword.lexeme);
+            self.errors.push(SyntaxError::at(keyword, &message));
+        }
+    }
+}
+
+impl stmt::Visitor<()> for Resolver<'_> {
+    fn visit_block_stmt(&mut self, _id: StmtId, stmt: &stmt::Block) {
+        for statement in &stmt.statements {
+            self.stmt(*statement);
+        }
+    }
+
+    fn visit_break_stmt(&mut self, _id: StmtId, stmt: &stmt::Break) {
+        self.jump(&stmt.keyword);
+    }
+
+    fn visit_continue_stmt(&mut self, _id: StmtId, stmt: &stmt::Continue) {
+        self.jump(&stmt.keyword);
+    }
+
+    fn visit_expression_stmt(&mut self, _id: StmtId, _stmt: &stmt::Expression) {}
+
+    // Desugar turns these into a `While`, but a tree that skipped it is
+    // checked the same way. The condition and increment are expressions,
+    // which have nothing to check yet.
+    fn visit_for_stmt(&mut self, _id: StmtId, stmt: &stmt::For) {
+        if let Some(initializer) = stmt.initializer {
+            self.stmt(initializer);
+        }
+        self.loop_body(stmt.body);
+    }
+
+    fn visit_for_in_stmt(&mut self, _id: StmtId, stmt: &stmt::ForIn) {
+        self.loop_body(stmt.body);
+    }
+
+    fn visit_if_stmt(&mut self, _id: StmtId, stmt: &stmt::If) {
+        self.stmt(stmt.then_branch);
+        if let Some(else_branch) = stmt.else_branch {
+            self.stmt(else_branch);
+        }
+    }
+
+    fn visit_print_stmt(&mut self, _id: StmtId, _stmt: &stmt::Print) {}
+
+    fn visit_var_stmt(&mut self, _id: StmtId, _stmt: &stmt::Var) {}
+
+    fn visit_while_stmt(&mut self, _id: StmtId, stmt: &stmt::While) {
+        self.loop_body(stmt.body);
+    }
+}
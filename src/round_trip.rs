@@ -0,0 +1,214 @@
+// Parser round trips: random expression trees are printed to source with the
+// `Formatter`, parsed again and compared through their RPN form, so any
+// mistake in precedence or associativity on either side shows up as a
+// different tree.
+
+use proptest::prelude::*;
+
+use crate::ast::{Ast, ExprId, Span};
+use crate::expr;
+use crate::formatter::Formatter;
+use crate::rpn_printer::RpnPrinter;
+use crate::stmt;
+use crate::{Parser, Scanner, Token, TokenType, Value};
+
+const NAMES: [&str; 4] = ["a", "b", "total", "x_1"];
+
+#[derive(Debug, Clone)]
+enum Tree {
+    Literal(Value),
+    Variable(&'static str),
+    Grouping(Box<Tree>),
+    Unary(TokenType, Box<Tree>),
+    Binary(Box<Tree>, TokenType, Box<Tree>),
+    Logical(Box<Tree>, TokenType, Box<Tree>),
+    Assign(&'static str, Box<Tree>),
+    Call(Box<Tree>, Vec<Tree>),
+    List(Vec<Tree>),
+    Index(Box<Tree>, Box<Tree>),
+    IndexSet(Box<Tree>, Box<Tree>, Box<Tree>),
+    Get(Box<Tree>, &'static str),
+    Map(Vec<(Tree, Tree)>),
+}
+
+fn lexeme(token_type: &TokenType) -> &'static str {
+    match token_type {
+        TokenType::Bang => "!",
+        TokenType::BangEqual => "!=",
+        TokenType::EqualEqual => "==",
+        TokenType::Greater => ">",
+        TokenType::GreaterEqual => ">=",
+        TokenType::Less => "<",
+        TokenType::LessEqual => "<=",
+        TokenType::Minus => "-",
+        TokenType::Plus => "+",
+        TokenType::Slash => "/",
+        TokenType::Star => "*",
+        TokenType::And => "and",
+        TokenType::Or => "or",
+        other => panic!("{other} is not an operator"),
+    }
+}
+
+fn token(token_type: TokenType, lexeme: &str) -> Token {
+    Token::new(token_type, lexeme.to_string(), Value::None, 1, 0)
+}
+
+fn operator(token_type: &TokenType) -> Token {
+    token(token_type.clone(), lexeme(token_type))
+}
+
+fn build(tree: &Tree, ast: &mut Ast) -> ExprId {
+    let span = Span::default();
+    match tree {
+        Tree::Literal(value) => ast.alloc_expr(expr::Literal::new(value.clone()), span),
+        Tree::Variable(name) => {
+            ast.alloc_expr(expr::Variable::new(token(TokenType::Identifier, name)), span)
+        }
+        Tree::Grouping(inner) => {
+            let inner = build(inner, ast);
+            ast.alloc_expr(expr::Grouping::new(inner), span)
+        }
+        Tree::Unary(op, right) => {
+            let right = build(right, ast);
+            ast.alloc_expr(expr::Unary::new(operator(op), right), span)
+        }
+        Tree::Binary(left, op, right) => {
+            let left = build(left, ast);
+            let right = build(right, ast);
+            ast.alloc_expr(expr::Binary::new(left, operator(op), right), span)
+        }
+        Tree::Logical(left, op, right) => {
+            let left = build(left, ast);
+            let right = build(right, ast);
+            ast.alloc_expr(expr::Logical::new(left, operator(op), right), span)
+        }
+        Tree::Assign(name, value) => {
+            let value = build(value, ast);
+            ast.alloc_expr(expr::Assign::new(token(TokenType::Identifier, name), value), span)
+        }
+        Tree::Call(callee, arguments) => {
+            let callee = build(callee, ast);
+            let arguments = arguments.iter().map(|argument| build(argument, ast)).collect();
+            ast.alloc_expr(expr::Call::new(callee, token(TokenType::RightParen, ")"), arguments), span)
+        }
+        Tree::List(elements) => {
+            let elements = elements.iter().map(|element| build(element, ast)).collect();
+            ast.alloc_expr(expr::List::new(token(TokenType::LeftBracket, "["), elements), span)
+        }
+        Tree::Index(object, index) => {
+            let object = build(object, ast);
+            let index = build(index, ast);
+            ast.alloc_expr(expr::Index::new(object, token(TokenType::LeftBracket, "["), index), span)
+        }
+        Tree::IndexSet(object, index, value) => {
+            let object = build(object, ast);
+            let index = build(index, ast);
+            let value = build(value, ast);
+            ast.alloc_expr(expr::IndexSet::new(object, token(TokenType::LeftBracket, "["), index, value), span)
+        }
+        Tree::Map(entries) => {
+            let keys = entries.iter().map(|(key, _)| build(key, ast)).collect();
+            let values = entries.iter().map(|(_, value)| build(value, ast)).collect();
+            ast.alloc_expr(expr::Map::new(token(TokenType::LeftBrace, "{"), keys, values), span)
+        }
+        Tree::Get(object, name) => {
+            let object = build(object, ast);
+            ast.alloc_expr(expr::Get::new(object, token(TokenType::Identifier, name)), span)
+        }
+    }
+}
+
+fn tree() -> impl Strategy<Value = Tree> {
+    // Negative numbers are not literals in Lox, they parse as a negation.
+    let leaf = prop_oneof![
+        (0u32..100_000).prop_map(|n| Tree::Literal(Value::Number(f64::from(n) / 8.0))),
+        "[a-z ]{0,6}".prop_map(|s| Tree::Literal(Value::String(s))),
+        any::<bool>().prop_map(|b| Tree::Literal(Value::Boolean(b))),
+        Just(Tree::Literal(Value::None)),
+        prop::sample::select(&NAMES[..]).prop_map(Tree::Variable),
+    ];
+
+    let binary = vec![
+        TokenType::BangEqual,
+        TokenType::EqualEqual,
+        TokenType::Greater,
+        TokenType::GreaterEqual,
+        TokenType::Less,
+        TokenType::LessEqual,
+        TokenType::Minus,
+        TokenType::Plus,
+        TokenType::Slash,
+        TokenType::Star,
+    ];
+
+    leaf.prop_recursive(6, 64, 2, move |inner| {
+        prop_oneof![
+            inner.clone().prop_map(|tree| Tree::Grouping(Box::new(tree))),
+            (prop::sample::select(vec![TokenType::Bang, TokenType::Minus]), inner.clone())
+                .prop_map(|(op, right)| Tree::Unary(op, Box::new(right))),
+            (inner.clone(), prop::sample::select(binary.clone()), inner.clone())
+                .prop_map(|(left, op, right)| Tree::Binary(Box::new(left), op, Box::new(right))),
+            (inner.clone(), prop::sample::select(vec![TokenType::And, TokenType::Or]), inner.clone())
+                .prop_map(|(left, op, right)| Tree::Logical(Box::new(left), op, Box::new(right))),
+            (prop::sample::select(&NAMES[..]), inner.clone())
+                .prop_map(|(name, value)| Tree::Assign(name, Box::new(value))),
+            (inner.clone(), prop::collection::vec(inner.clone(), 0..3))
+                .prop_map(|(callee, arguments)| Tree::Call(Box::new(callee), arguments)),
+            prop::collection::vec(inner.clone(), 0..3).prop_map(Tree::List),
+            (inner.clone(), inner.clone()).prop_map(|(object, index)| Tree::Index(Box::new(object), Box::new(index))),
+            (inner.clone(), inner.clone(), inner.clone()).prop_map(|(object, index, value)| {
+                Tree::IndexSet(Box::new(object), Box::new(index), Box::new(value))
+            }),
+            (inner.clone(), prop::sample::select(&NAMES[..]))
+                .prop_map(|(object, name)| Tree::Get(Box::new(object), name)),
+            prop::collection::vec((inner.clone(), inner), 0..3).prop_map(Tree::Map),
+        ]
+    })
+}
+
+// Parses `source`, which must be a single expression statement, and returns
+// the expression in RPN.
+fn parse_expression(source: &str) -> String {
+    let mut scanner = Scanner::new(source.to_string());
+    let tokens = scanner.scan_tokens().to_vec();
+    let mut ast = Ast::new();
+    let statements = Parser::new(tokens, &mut ast).parse().expect(source);
+    assert_eq!(statements.len(), 1, "{source}");
+
+    let statement = ast.stmt(statements[0]);
+    let statement = statement
+        .as_any()
+        .downcast_ref::<stmt::Expression>()
+        .expect("an expression statement");
+    RpnPrinter::new(&ast).print(statement.expression)
+}
+
+#[test]
+fn rpn_of_the_book_example() {
+    assert_eq!(parse_expression("(1 + 2) * (4 - 3);"), "1 2 + 4 3 - *");
+}
+
+#[test]
+fn rpn_shows_associativity() {
+    assert_eq!(parse_expression("1 - 2 - 3;"), "1 2 - 3 -");
+    assert_eq!(parse_expression("1 - (2 - 3);"), "1 2 3 - -");
+    assert_eq!(parse_expression("a = b = 1;"), "1 b = a =");
+    assert_eq!(parse_expression("-!-a;"), "a neg ! neg");
+    assert_eq!(parse_expression("-f(a, b)(c);"), "f a b call/2 c call/1 neg");
+    assert_eq!(parse_expression("a.b[0] = c[1] = [d];"), "a .b 0 c 1 d list/1 []= []=");
+    assert_eq!(parse_expression("({a: 1, \"b\": {}})[a];"), "a 1 \"b\" map/0 map/2 a []");
+}
+
+proptest! {
+    #[test]
+    fn formatted_expressions_parse_back_to_the_same_tree(tree in tree()) {
+        let mut ast = Ast::new();
+        let expr = build(&tree, &mut ast);
+        let statement = ast.alloc_stmt(stmt::Expression::new(expr), Span::default());
+        let source = Formatter::new(&ast, "", vec![]).format(&[statement]);
+
+        let expected = RpnPrinter::new(&ast).print(expr);
+        prop_assert_eq!(parse_expression(&source), expected, "source: {}", source);
+    }
+}
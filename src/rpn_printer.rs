@@ -0,0 +1,121 @@
+use crate::ast::{Ast, ExprId};
+use crate::expr;
+use crate::{TokenType, Value};
+
+/// Prints expressions in Reverse Polish Notation, `(1 + 2) * 3` as
+/// `1 2 + 3 *`. Postfix needs no parentheses, so two trees print the same
+/// only if they have the same shape, which makes it a cheap way to compare
+/// what the parser built. Groupings leave no trace and unary minus is `neg`
+/// to tell it apart from subtraction.
+///
+/// ```
+/// use rlox::ast::Ast;
+/// use rlox::rpn_printer::RpnPrinter;
+/// use rlox::{Parser, Scanner};
+///
+/// let tokens = Scanner::new("(1 + 2) * -x".to_string()).scan_tokens().to_vec();
+/// let mut ast = Ast::new();
+/// let expr = Parser::new(tokens, &mut ast).parse_expression().unwrap();
+/// assert_eq!(RpnPrinter::new(&ast).print(expr), "1 2 + x neg *");
+/// ```
+pub struct RpnPrinter<'a> {
+    ast: &'a Ast,
+}
+
+impl<'a> RpnPrinter<'a> {
+    pub fn new(ast: &'a Ast) -> Self {
+        RpnPrinter { ast }
+    }
+
+    pub fn print(&mut self, expr: ExprId) -> String {
+        self.ast.accept_expr(expr, self)
+    }
+}
+
+fn literal_to_string(literal: &Value) -> String {
+    match literal {
+        Value::None => "nil".to_string(),
+        Value::String(string) => format!("{string:?}"),
+        Value::Number(number) => number.to_string(),
+        Value::Boolean(boolean) => boolean.to_string(),
+        Value::Native(_) | Value::List(_) | Value::Map(_) => literal.to_string(),
+    }
+}
+
+impl expr::Visitor<String> for RpnPrinter<'_> {
+    fn visit_assign_expr(&mut self, _id: ExprId, expr: &expr::Assign) -> String {
+        format!("{} {} =", self.print(expr.value), expr.name.lexeme)
+    }
+
+    fn visit_binary_expr(&mut self, _id: ExprId, expr: &expr::Binary) -> String {
+        let left = self.print(expr.left);
+        let right = self.print(expr.right);
+        format!("{left} {right} {}", expr.operator.lexeme)
+    }
+
+    // The argument count goes with the operator, or `f a b call` could also
+    // be `f(a)` applied to `b`.
+    fn visit_call_expr(&mut self, _id: ExprId, expr: &expr::Call) -> String {
+        let mut parts = vec![self.print(expr.callee)];
+        parts.extend(expr.arguments.iter().map(|argument| self.print(*argument)));
+        parts.push(format!("call/{}", expr.arguments.len()));
+        parts.join(" ")
+    }
+
+    fn visit_get_expr(&mut self, _id: ExprId, expr: &expr::Get) -> String {
+        format!("{} .{}", self.print(expr.object), expr.name.lexeme)
+    }
+
+    fn visit_grouping_expr(&mut self, _id: ExprId, expr: &expr::Grouping) -> String {
+        self.print(expr.expression)
+    }
+
+    fn visit_index_expr(&mut self, _id: ExprId, expr: &expr::Index) -> String {
+        format!("{} {} []", self.print(expr.object), self.print(expr.index))
+    }
+
+    fn visit_index_set_expr(&mut self, _id: ExprId, expr: &expr::IndexSet) -> String {
+        let object = self.print(expr.object);
+        let index = self.print(expr.index);
+        format!("{object} {index} {} []=", self.print(expr.value))
+    }
+
+    // Counted like calls, `[a, b]` is `a b list/2`.
+    fn visit_list_expr(&mut self, _id: ExprId, expr: &expr::List) -> String {
+        let mut parts = expr.elements.iter().map(|element| self.print(*element)).collect::<Vec<_>>();
+        parts.push(format!("list/{}", expr.elements.len()));
+        parts.join(" ")
+    }
+
+    fn visit_literal_expr(&mut self, _id: ExprId, expr: &expr::Literal) -> String {
+        literal_to_string(&expr.value)
+    }
+
+    fn visit_logical_expr(&mut self, _id: ExprId, expr: &expr::Logical) -> String {
+        let left = self.print(expr.left);
+        let right = self.print(expr.right);
+        format!("{left} {right} {}", expr.operator.lexeme)
+    }
+
+    fn visit_map_expr(&mut self, _id: ExprId, expr: &expr::Map) -> String {
+        let mut parts = vec![];
+        for (key, value) in expr.keys.iter().zip(&expr.values) {
+            parts.push(self.print(*key));
+            parts.push(self.print(*value));
+        }
+        parts.push(format!("map/{}", expr.keys.len()));
+        parts.join(" ")
+    }
+
+    fn visit_unary_expr(&mut self, _id: ExprId, expr: &expr::Unary) -> String {
+        let operator = match expr.operator.token_type {
+            TokenType::Minus => "neg",
+            _ => &expr.operator.lexeme,
+        };
+        format!("{} {operator}", self.print(expr.right))
+    }
+
+    fn visit_variable_expr(&mut self, _id: ExprId, expr: &expr::Variable) -> String {
+        expr.name.lexeme.clone()
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+
+use crate::{SyntaxError, Token, TokenType, Value};
+
+/// Reserved words and the token each one scans to.
+pub fn load_keywords() -> HashMap<String, TokenType> {
+    let mut keywords = HashMap::new();
+    keywords.insert("and".to_string(), TokenType::And);
+    keywords.insert("break".to_string(), TokenType::Break);
+    keywords.insert("class".to_string(), TokenType::Class);
+    keywords.insert("continue".to_string(), TokenType::Continue);
+    keywords.insert("else".to_string(), TokenType::Else);
+    keywords.insert("false".to_string(), TokenType::False);
+    keywords.insert("for".to_string(), TokenType::For);
+    keywords.insert("fun".to_string(), TokenType::Fun);
+    keywords.insert("if".to_string(), TokenType::If);
+    keywords.insert("in".to_string(), TokenType::In);
+    keywords.insert("nil".to_string(), TokenType::Nil);
+    keywords.insert("or".to_string(), TokenType::Or);
+    keywords.insert("print".to_string(), TokenType::Print);
+    keywords.insert("return".to_string(), TokenType::Return);
+    keywords.insert("super".to_string(), TokenType::Super);
+    keywords.insert("this".to_string(), TokenType::This);
+    keywords.insert("true".to_string(), TokenType::True);
+    keywords.insert("var".to_string(), TokenType::Var);
+    keywords.insert("while".to_string(), TokenType::While);
+    keywords
+}
+
+pub struct Scanner {
+    source: String,
+    tokens: Vec<Token>,
+    start: usize,
+    current: usize,
+    line: usize,
+    keywords: HashMap<String, TokenType>,
+    keep_comments: bool,
+    errors: Vec<SyntaxError>,
+}
+
+impl Scanner {
+    pub fn new(source: String) -> Self {
+        Scanner {
+            source,
+            tokens: vec![],
+            start: 0,
+            current: 0,
+            line: 1,
+            keywords: load_keywords(),
+            keep_comments: false,
+            errors: vec![],
+        }
+    }
+
+    /// Scanner that also emits `//` comments as `Comment` tokens, for tools
+    /// like the formatter that need to put them back. The `Parser` does not
+    /// know about them, so they have to be filtered out before parsing.
+    pub fn with_comments(source: String) -> Self {
+        Scanner {
+            keep_comments: true,
+            ..Scanner::new(source)
+        }
+    }
+
+    pub fn errors(&self) -> &[SyntaxError] {
+        &self.errors
+    }
+
+    pub fn scan_tokens(&mut self) -> &[Token] {
+        while !self.is_at_end() {
+            self.start = self.current;
+            self.scan_token();
+        }
+
+        self.tokens.push(Token {
+            token_type: TokenType::EOF,
+            lexeme: "".to_string(),
+            literal: Value::None,
+            line: self.line,
+            start: self.current,
+        });
+
+        &self.tokens[..]
+    }
+
+    // `start` and `current` are byte offsets into the source, which is what
+    // spans hold, so anything outside ASCII has to be stepped over whole.
+    fn scan_token(&mut self) {
+        let char = self.advance();
+
+        match char {
+            '(' => self.add_token(TokenType::LeftParen),
+            ')' => self.add_token(TokenType::RightParen),
+            '{' => self.add_token(TokenType::LeftBrace),
+            '}' => self.add_token(TokenType::RightBrace),
+            '[' => self.add_token(TokenType::LeftBracket),
+            ']' => self.add_token(TokenType::RightBracket),
+            ':' => self.add_token(TokenType::Colon),
+            ',' => self.add_token(TokenType::Comma),
+            '.' => self.add_token(TokenType::Dot),
+            '-' => self.add_token(TokenType::Minus),
+            '+' => self.add_token(TokenType::Plus),
+            ';' => self.add_token(TokenType::Semicolon),
+            '*' => self.add_token(TokenType::Star),
+            '!' => {
+                let c = if self.check('=') {
+                    TokenType::BangEqual
+                } else {
+                    TokenType::Bang
+                };
+                self.add_token(c);
+            }
+            '=' => {
+                let c = if self.check('=') {
+                    TokenType::EqualEqual
+                } else {
+                    TokenType::Equal
+                };
+                self.add_token(c);
+            }
+            '<' => {
+                let c = if self.check('=') {
+                    TokenType::LessEqual
+                } else {
+                    TokenType::Less
+                };
+                self.add_token(c);
+            }
+            '>' => {
+                let c = if self.check('=') {
+                    TokenType::GreaterEqual
+                } else {
+                    TokenType::Greater
+                };
+                self.add_token(c);
+            }
+            '/' => {
+                if self.check('/') {
+                    while self.peek() != '\n' && !self.is_at_end() {
+                        self.advance();
+                    }
+                    if self.keep_comments {
+                        self.add_token(TokenType::Comment);
+                    }
+                } else {
+                    self.add_token(TokenType::Slash);
+                };
+            }
+            ' ' => (),
+            '\r' => (),
+            '\t' => (),
+            '\n' => self.line += 1,
+            '"' => self.handle_string(),
+            '0'..='9' => self.handle_number(),
+            _ => {
+                if char.is_alphabetic() || char == '_' {
+                    self.identifier()
+                } else {
+                    self.error("Unexpected character.", false)
+                }
+            }
+        }
+    }
+
+    fn handle_number(&mut self) {
+        while self.peek().is_ascii_digit() {
+            self.advance();
+        }
+        if self.peek() == '.' && self.peek_next().is_ascii_digit() {
+            self.advance();
+            while self.peek().is_ascii_digit() {
+                self.advance();
+            }
+        }
+
+        let number = self.source[self.start..self.current]
+            .trim()
+            .parse::<f64>()
+            .expect("digits with at most one dot always parse as a float");
+        self.add_token_literal(TokenType::Number, Value::Number(number))
+    }
+
+    fn handle_string(&mut self) {
+        while self.peek() != '"' && !self.is_at_end() {
+            if self.peek() == '\n' {
+                self.line += 1;
+            }
+            self.advance();
+        }
+        if self.is_at_end() {
+            self.error("Unterminated string.", true);
+            return;
+        }
+        self.advance();
+
+        let text = &self.source[self.start + 1..self.current - 1];
+        self.add_token_literal(TokenType::String, Value::String(text.to_string()));
+    }
+
+    fn error(&mut self, message: &str, at_end: bool) {
+        self.errors.push(SyntaxError {
+            line: self.line,
+            place: String::new(),
+            message: message.to_string(),
+            at_end,
+        });
+    }
+
+    fn add_token(&mut self, token_type: TokenType) {
+        self.add_token_literal(token_type, Value::None);
+    }
+
+    fn add_token_literal(&mut self, token_type: TokenType, literal: Value) {
+        let lexeme = &self.source[self.start..self.current];
+        self.tokens.push(Token {
+            token_type,
+            lexeme: lexeme.to_string(),
+            literal,
+            line: self.line,
+            start: self.start,
+        })
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.current >= self.source.len()
+    }
+
+    fn advance(&mut self) -> char {
+        let char = self.peek();
+        self.current += char.len_utf8();
+        char
+    }
+
+    fn check(&mut self, expected: char) -> bool {
+        if self.is_at_end() || self.peek() != expected {
+            return false;
+        }
+
+        self.advance();
+        true
+    }
+
+    fn peek(&self) -> char {
+        self.source[self.current..].chars().next().unwrap_or('\0')
+    }
+
+    fn peek_next(&self) -> char {
+        self.source[self.current..].chars().nth(1).unwrap_or('\0')
+    }
+
+    fn identifier(&mut self) {
+        while self.peek().is_alphanumeric() || self.peek() == '_' {
+            self.advance();
+        }
+        let text = self.source[self.start..self.current].trim();
+
+        match self.keywords.get(text) {
+            Some(t) => self.add_token(t.clone()),
+            None => self.add_token(TokenType::Identifier),
+        };
+    }
+}
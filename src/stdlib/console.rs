@@ -0,0 +1,22 @@
+use std::io::Write;
+
+use crate::{Interpreter, RuntimeError, Value};
+
+pub fn define<W: Write>(interpreter: &mut Interpreter<W>) {
+    // A line from the interpreter's input without its newline, or nil once
+    // there is no more. The host chose the input, so it is no way out of a
+    // sandbox and works without `allow_io`.
+    let input = interpreter.input();
+    interpreter.define_native("input", 0, move |_| {
+        let mut line = String::new();
+        let read = input
+            .borrow_mut()
+            .read_line(&mut line)
+            .map_err(|error| RuntimeError::native(&format!("Can't read the input: {error}.")))?;
+        if read == 0 {
+            return Ok(Value::None);
+        }
+        let line = line.strip_suffix('\n').unwrap_or(&line);
+        Ok(Value::from(line.strip_suffix('\r').unwrap_or(line)))
+    });
+}
@@ -0,0 +1,96 @@
+// Methods of lists, looked up by name when a script does `list.name`. Each
+// one is a native bound to the list it was taken from. Also `range`, for
+// looping over numbers.
+
+use std::cell::{Cell, RefCell};
+use std::io::Write;
+use std::rc::Rc;
+
+use crate::{argument, wrong_argument, Interpreter, Items, NativeFunction, RuntimeError, Value};
+
+pub type List = Rc<RefCell<Items>>;
+
+pub fn define<W: Write>(interpreter: &mut Interpreter<W>) {
+    // Numbers from `start` up to `end`, not included, `step` apart; a
+    // negative step counts down. It gives back a function for `for` to call,
+    // so they are only made as the loop gets to them.
+    interpreter.define_native("range", 3, |arguments| {
+        let start = argument::<f64>(arguments, 0)?;
+        let end = argument::<f64>(arguments, 1)?;
+        let step = argument::<f64>(arguments, 2)?;
+        if step == 0.0 || step.is_nan() {
+            return Err(wrong_argument(2, "a number other than 0"));
+        }
+
+        let count = Cell::new(0.0);
+        let next = NativeFunction::new("range", 0, move |_| {
+            let number = start + count.get() * step;
+            if (step > 0.0 && number >= end) || (step < 0.0 && number <= end) {
+                return Ok(Value::None);
+            }
+            count.set(count.get() + 1.0);
+            Ok(Value::Number(number))
+        });
+        Ok(Value::Native(Rc::new(next)))
+    });
+}
+
+pub fn method(list: &List, name: &str) -> Option<NativeFunction> {
+    let list = Rc::clone(list);
+    let native = match name {
+        "push" => NativeFunction::new(name, 1, move |arguments| {
+            list.borrow_mut().push(arguments[0].clone());
+            Ok(Value::None)
+        }),
+        "pop" => NativeFunction::new(name, 0, move |_| {
+            list.borrow_mut()
+                .pop()
+                .ok_or_else(|| RuntimeError::native("Can't pop from an empty list."))
+        }),
+        "len" => NativeFunction::new(name, 0, move |_| Ok(Value::Number(list.borrow().len() as f64))),
+        // Anywhere from the start to right after the last item.
+        "insert" => NativeFunction::new(name, 2, move |arguments| {
+            let len = list.borrow().len();
+            let index = if arguments[0] == Value::Number(len as f64) {
+                len
+            } else {
+                index(&arguments[0], len).map_err(|message| RuntimeError::native(&message))?
+            };
+            list.borrow_mut().insert(index, arguments[1].clone());
+            Ok(Value::None)
+        }),
+        "remove" => NativeFunction::new(name, 1, move |arguments| {
+            let len = list.borrow().len();
+            let index = index(&arguments[0], len).map_err(|message| RuntimeError::native(&message))?;
+            Ok(list.borrow_mut().remove(index))
+        }),
+        _ => return None,
+    };
+    Some(native)
+}
+
+/// The item the method `name` puts in its list, if it is one that does. The
+/// interpreter charges it to the heap before the method runs.
+pub fn stored<'a>(name: &str, arguments: &'a [Value]) -> Option<&'a Value> {
+    match (name, arguments) {
+        ("push", [item]) | ("insert", [_, item]) => Some(item),
+        _ => None,
+    }
+}
+
+/// `index` as a position in a list of `len` items, or why it isn't one.
+pub fn index(index: &Value, len: usize) -> Result<usize, String> {
+    let Value::Number(number) = index else {
+        return Err("Index must be a number.".to_string());
+    };
+    if number.fract() != 0.0 {
+        return Err(format!("Index must be a whole number, got {number}."));
+    }
+    if *number < 0.0 {
+        return Err(format!("Index can't be negative, got {number}."));
+    }
+    if *number >= len as f64 {
+        return Err(format!("Index {number} is out of bounds for a list of length {len}."));
+    }
+    Ok(*number as usize)
+}
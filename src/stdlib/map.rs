@@ -0,0 +1,51 @@
+// Methods of maps, bound to their map the same way as the ones of lists.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::{Entries, Key, NativeFunction, RuntimeError, Value};
+
+pub type Map = Rc<RefCell<Entries>>;
+
+pub fn method(map: &Map, name: &str) -> Option<NativeFunction> {
+    let map = Rc::clone(map);
+    let native = match name {
+        "has" => NativeFunction::new(name, 1, move |arguments| {
+            let key = key(&arguments[0]).map_err(|message| RuntimeError::native(&message))?;
+            Ok(Value::Boolean(map.borrow().contains_key(&key)))
+        }),
+        // Gives back the value that was there. The other keys keep their
+        // order.
+        "remove" => NativeFunction::new(name, 1, move |arguments| {
+            let key = key(&arguments[0]).map_err(|message| RuntimeError::native(&message))?;
+            map.borrow_mut()
+                .shift_remove(&key)
+                .ok_or_else(|| RuntimeError::native(&missing(&key)))
+        }),
+        "keys" => NativeFunction::new(name, 0, move |_| {
+            Ok(Value::list(map.borrow().keys().map(|key| key.value().clone()).collect()))
+        }),
+        "values" => NativeFunction::new(name, 0, move |_| Ok(Value::list(map.borrow().values().cloned().collect()))),
+        "len" => NativeFunction::new(name, 0, move |_| Ok(Value::Number(map.borrow().len() as f64))),
+        _ => return None,
+    };
+    Some(native)
+}
+
+/// `value` as a map key, or why it can't be one.
+pub fn key(value: &Value) -> Result<Key, String> {
+    Key::new(value.clone()).ok_or_else(|| {
+        format!(
+            "Only strings, numbers, booleans and nil can be map keys, got a {}.",
+            value.type_name()
+        )
+    })
+}
+
+/// The error for looking up a key the map doesn't have.
+pub fn missing(key: &Key) -> String {
+    match key.value() {
+        Value::String(string) => format!("Key {string:?} not found."),
+        key => format!("Key {key} not found."),
+    }
+}
@@ -0,0 +1,50 @@
+use std::cell::Cell;
+use std::io::Write;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{argument, Interpreter, Value};
+
+pub fn define<W: Write>(interpreter: &mut Interpreter<W>) {
+    interpreter.define_native("sqrt", 1, |arguments| Ok(Value::Number(argument::<f64>(arguments, 0)?.sqrt())));
+
+    interpreter.define_native("floor", 1, |arguments| Ok(Value::Number(argument::<f64>(arguments, 0)?.floor())));
+
+    interpreter.define_native("abs", 1, |arguments| Ok(Value::Number(argument::<f64>(arguments, 0)?.abs())));
+
+    interpreter.define_native("min", 2, |arguments| {
+        Ok(Value::Number(argument::<f64>(arguments, 0)?.min(argument(arguments, 1)?)))
+    });
+
+    interpreter.define_native("max", 2, |arguments| {
+        Ok(Value::Number(argument::<f64>(arguments, 0)?.max(argument(arguments, 1)?)))
+    });
+
+    // `random()` is in [0, 1). It starts from the clock; `seed(n)` makes the
+    // numbers that follow the same on every run.
+    let state = Rc::new(Cell::new(clock_seed()));
+    let random_state = Rc::clone(&state);
+    interpreter.define_native("random", 0, move |_| {
+        let bits = next(&random_state) >> 11;
+        Ok(Value::Number(bits as f64 / (1u64 << 53) as f64))
+    });
+    interpreter.define_native("seed", 1, move |arguments| {
+        state.set(argument::<f64>(arguments, 0)?.to_bits());
+        Ok(Value::None)
+    });
+}
+
+fn clock_seed() -> u64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    now.as_nanos() as u64
+}
+
+// SplitMix64: tiny, and good enough for scripts.
+fn next(state: &Cell<u64>) -> u64 {
+    let seed = state.get().wrapping_add(0x9e37_79b9_7f4a_7c15);
+    state.set(seed);
+    let mut z = seed;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
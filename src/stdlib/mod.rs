@@ -0,0 +1,26 @@
+// Natives every interpreter starts with, one module per area. Arguments are
+// checked with `argument` and `wrong_argument`, so a bad one always reads
+// "Argument N must be ...".
+
+use std::io::Write;
+
+use crate::Interpreter;
+
+mod console;
+pub(crate) mod list;
+pub(crate) mod map;
+mod math;
+mod strings;
+mod system;
+mod time;
+mod types;
+
+pub fn define<W: Write>(interpreter: &mut Interpreter<W>) {
+    console::define(interpreter);
+    list::define(interpreter);
+    math::define(interpreter);
+    strings::define(interpreter);
+    system::define(interpreter);
+    time::define(interpreter);
+    types::define(interpreter);
+}
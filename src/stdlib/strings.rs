@@ -0,0 +1,34 @@
+use std::io::Write;
+
+use crate::{argument, Interpreter, Value};
+
+pub fn define<W: Write>(interpreter: &mut Interpreter<W>) {
+    // `length` characters from `start`, counted in characters. Going past
+    // the end just gives less.
+    interpreter.define_native("substr", 3, |arguments| {
+        let string = argument::<String>(arguments, 0)?;
+        let start = argument::<usize>(arguments, 1)?;
+        let length = argument::<usize>(arguments, 2)?;
+        Ok(Value::from(string.chars().skip(start).take(length).collect::<String>()))
+    });
+
+    interpreter.define_native("upper", 1, |arguments| {
+        Ok(Value::from(argument::<String>(arguments, 0)?.to_uppercase()))
+    });
+
+    interpreter.define_native("lower", 1, |arguments| {
+        Ok(Value::from(argument::<String>(arguments, 0)?.to_lowercase()))
+    });
+
+    // An empty separator splits into characters.
+    interpreter.define_native("split", 2, |arguments| {
+        let string = argument::<String>(arguments, 0)?;
+        let separator = argument::<String>(arguments, 1)?;
+        let parts = if separator.is_empty() {
+            string.chars().map(|char| Value::from(char.to_string())).collect()
+        } else {
+            string.split(separator.as_str()).map(Value::from).collect()
+        };
+        Ok(Value::list(parts))
+    });
+}
@@ -0,0 +1,56 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use crate::{argument, Interpreter, RuntimeError, Value};
+
+// Everything here reaches outside the interpreter, so they are all I/O
+// natives and need `Limits::allow_io`.
+pub fn define<W: Write>(interpreter: &mut Interpreter<W>) {
+    interpreter.define_io_native("readFile", 1, |arguments| {
+        let path = argument::<String>(arguments, 0)?;
+        fs::read_to_string(&path)
+            .map(Value::from)
+            .map_err(|error| failed("read", &path, error))
+    });
+
+    interpreter.define_io_native("writeFile", 2, |arguments| {
+        let path = argument::<String>(arguments, 0)?;
+        let content = argument::<String>(arguments, 1)?;
+        fs::write(&path, content)
+            .map(|_| Value::None)
+            .map_err(|error| failed("write", &path, error))
+    });
+
+    interpreter.define_io_native("exists", 1, |arguments| {
+        Ok(Value::from(Path::new(&argument::<String>(arguments, 0)?).exists()))
+    });
+
+    // Names of the entries, sorted so scripts behave the same everywhere.
+    interpreter.define_io_native("listDir", 1, |arguments| {
+        let path = argument::<String>(arguments, 0)?;
+        let mut names = fs::read_dir(&path)
+            .and_then(|entries| {
+                entries
+                    .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().into_owned()))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .map_err(|error| failed("list", &path, error))?;
+        names.sort();
+        Ok(Value::list(names.into_iter().map(Value::from).collect()))
+    });
+
+    // nil when the variable is not set.
+    interpreter.define_io_native("getenv", 1, |arguments| {
+        Ok(Value::from(std::env::var(argument::<String>(arguments, 0)?).ok()))
+    });
+
+    let args = interpreter.args();
+    interpreter.define_io_native("args", 0, move |_| {
+        Ok(Value::list(args.borrow().iter().map(|arg| Value::from(arg.as_str())).collect()))
+    });
+}
+
+fn failed(action: &str, path: &str, error: std::io::Error) -> RuntimeError {
+    RuntimeError::native(&format!("Can't {action} '{path}': {error}."))
+}
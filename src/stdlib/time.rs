@@ -0,0 +1,12 @@
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{Interpreter, Value};
+
+pub fn define<W: Write>(interpreter: &mut Interpreter<W>) {
+    // Seconds since the epoch, for timing things like in the book.
+    interpreter.define_native("clock", 0, |_| {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        Ok(Value::Number(now.as_secs_f64()))
+    });
+}
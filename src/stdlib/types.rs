@@ -0,0 +1,28 @@
+use std::io::Write;
+
+use crate::{wrong_argument, Interpreter, Value};
+
+pub fn define<W: Write>(interpreter: &mut Interpreter<W>) {
+    interpreter.define_native("type", 1, |arguments| Ok(Value::from(arguments[0].type_name())));
+
+    interpreter.define_native("str", 1, |arguments| Ok(Value::from(arguments[0].to_string())));
+
+    // Numbers written like in Lox, or nil so scripts can check the input.
+    interpreter.define_native("num", 1, |arguments| match &arguments[0] {
+        Value::Number(number) => Ok(Value::Number(*number)),
+        Value::String(string) => {
+            let text = string.trim();
+            let looks_like_number = text.bytes().all(|byte| byte.is_ascii_digit() || byte == b'.' || byte == b'-');
+            Ok(Value::from(text.parse::<f64>().ok().filter(|_| looks_like_number)))
+        }
+        _ => Err(wrong_argument(0, "a string or a number")),
+    });
+
+    // Characters rather than bytes, so "ñ" is 1 long.
+    interpreter.define_native("len", 1, |arguments| match &arguments[0] {
+        Value::String(string) => Ok(Value::Number(string.chars().count() as f64)),
+        Value::List(list) => Ok(Value::Number(list.borrow().len() as f64)),
+        Value::Map(map) => Ok(Value::Number(map.borrow().len() as f64)),
+        _ => Err(wrong_argument(0, "a string, a list or a map")),
+    });
+}
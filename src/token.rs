@@ -0,0 +1,441 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+
+use indexmap::IndexMap;
+
+use crate::ast::Span;
+use crate::NativeFunction;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenType {
+    LeftParen,
+    RightParen,
+    LeftBrace,
+    RightBrace,
+    LeftBracket,
+    RightBracket,
+    Colon,
+    Comma,
+    Dot,
+    Minus,
+    Plus,
+    Semicolon,
+    Slash,
+    Star,
+
+    Bang,
+    BangEqual,
+    Equal,
+    EqualEqual,
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual,
+
+    Identifier,
+    String,
+    Number,
+
+    And,
+    Break,
+    Class,
+    Continue,
+    Else,
+    False,
+    Fun,
+    For,
+    If,
+    In,
+    Nil,
+    Or,
+    Print,
+    Return,
+    Super,
+    This,
+    True,
+    Var,
+    While,
+
+    Comment,
+    EOF,
+}
+
+impl core::fmt::Display for TokenType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let token_str = match self {
+            TokenType::LeftParen => "LeftParen",
+            TokenType::RightParen => "RightParen",
+            TokenType::LeftBrace => "LeftBrace",
+            TokenType::RightBrace => "RightBrace",
+            TokenType::LeftBracket => "LeftBracket",
+            TokenType::RightBracket => "RightBracket",
+            TokenType::Colon => "Colon",
+            TokenType::Comma => "Comma",
+            TokenType::Dot => "Dot",
+            TokenType::Minus => "Minus",
+            TokenType::Plus => "Plus",
+            TokenType::Semicolon => "Semicolon",
+            TokenType::Slash => "Slash",
+            TokenType::Star => "Star",
+
+            TokenType::Bang => "Bang",
+            TokenType::BangEqual => "BangEqual",
+            TokenType::Equal => "Equal",
+            TokenType::EqualEqual => "EqualEqual",
+            TokenType::Greater => "Greater",
+            TokenType::GreaterEqual => "GreaterEqual",
+            TokenType::Less => "Less",
+            TokenType::LessEqual => "LessEqual",
+
+            TokenType::Identifier => "Identifier",
+            TokenType::String => "String",
+            TokenType::Number => "Number",
+
+            TokenType::And => "And",
+            TokenType::Break => "Break",
+            TokenType::Class => "Class",
+            TokenType::Continue => "Continue",
+            TokenType::Else => "Else",
+            TokenType::False => "False",
+            TokenType::Fun => "Fun",
+            TokenType::For => "For",
+            TokenType::If => "If",
+            TokenType::In => "In",
+            TokenType::Nil => "Nil",
+            TokenType::Or => "Or",
+            TokenType::Print => "Print",
+            TokenType::Return => "Return",
+            TokenType::Super => "Super",
+            TokenType::This => "This",
+            TokenType::True => "True",
+            TokenType::Var => "Var",
+            TokenType::While => "While",
+
+            TokenType::Comment => "Comment",
+            TokenType::EOF => "EOF",
+        };
+        write!(f, "{}", token_str)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    String(String),
+    Number(f64),
+    Boolean(bool),
+    None,
+    Native(Rc<NativeFunction>),
+    /// Shared: copying a list gives another reference to the same one.
+    List(Rc<RefCell<Items>>),
+    /// Shared like lists. Keys stay in the order they were first added.
+    Map(Rc<RefCell<Entries>>),
+}
+
+impl Value {
+    pub fn list(items: Vec<Value>) -> Self {
+        Value::List(Rc::new(RefCell::new(Items(items))))
+    }
+
+    pub fn map(entries: impl IntoIterator<Item = (Key, Value)>) -> Self {
+        Value::Map(Rc::new(RefCell::new(Entries(entries.into_iter().collect()))))
+    }
+
+    /// What `type()` says the value is.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::Number(_) => "number",
+            Value::Boolean(_) => "boolean",
+            Value::None => "nil",
+            Value::Native(_) => "function",
+            Value::List(_) => "list",
+            Value::Map(_) => "map",
+        }
+    }
+}
+
+/// What a list holds, used like the `Vec` inside it.
+#[derive(Default)]
+pub struct Items(Vec<Value>);
+
+/// What a map holds, used like the `IndexMap` inside it.
+#[derive(Default)]
+pub struct Entries(IndexMap<Key, Value>);
+
+impl Deref for Items {
+    type Target = Vec<Value>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Items {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl Deref for Entries {
+    type Target = IndexMap<Key, Value>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Entries {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl fmt::Debug for Items {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Debug for Entries {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+// Dropping the last reference to a list drops its items, and a list inside a
+// list inside a list... would be dropped recursively, as deep as a script
+// cares to nest them. Instead the items of every list and map going away
+// with this one are moved to a stack here, so each is dropped already empty.
+impl Drop for Items {
+    fn drop(&mut self) {
+        drop_all(std::mem::take(&mut self.0));
+    }
+}
+
+impl Drop for Entries {
+    fn drop(&mut self) {
+        drop_all(self.0.drain(..).flat_map(|(key, value)| [key.0, value]).collect());
+    }
+}
+
+fn drop_all(mut pending: Vec<Value>) {
+    while let Some(mut value) = pending.pop() {
+        match &mut value {
+            Value::List(list) => {
+                if let Some(items) = Rc::get_mut(list) {
+                    pending.append(&mut items.get_mut().0);
+                }
+            }
+            Value::Map(map) => {
+                if let Some(entries) = Rc::get_mut(map) {
+                    pending.extend(entries.get_mut().0.drain(..).flat_map(|(key, value)| [key.0, value]));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A value that can be a map key: the ones compared by what they hold, not
+/// by identity, so the same key can be written again to find an entry.
+#[derive(Debug, Clone)]
+pub struct Key(Value);
+
+impl Key {
+    /// `None` for values that can't be keys, like lists.
+    pub fn new(value: Value) -> Option<Self> {
+        match value {
+            Value::String(_) | Value::Number(_) | Value::Boolean(_) | Value::None => Some(Key(value)),
+            _ => None,
+        }
+    }
+
+    pub fn value(&self) -> &Value {
+        &self.0
+    }
+
+    // `0` and `-0` are `==`, so they have to be the same key.
+    fn number_bits(number: f64) -> u64 {
+        if number == 0.0 {
+            0
+        } else {
+            number.to_bits()
+        }
+    }
+}
+
+impl PartialEq for Key {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.0, &other.0) {
+            (Value::Number(left), Value::Number(right)) => Key::number_bits(*left) == Key::number_bits(*right),
+            (left, right) => left == right,
+        }
+    }
+}
+
+impl Eq for Key {}
+
+impl Hash for Key {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(&self.0).hash(state);
+        match &self.0 {
+            Value::String(string) => string.hash(state),
+            Value::Number(number) => Key::number_bits(*number).hash(state),
+            Value::Boolean(boolean) => boolean.hash(state),
+            _ => {}
+        }
+    }
+}
+
+// Lists are equal only to themselves, like functions: two lists with the same
+// items are still two lists, and one can be changed without the other.
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::String(left), Value::String(right)) => left == right,
+            (Value::Number(left), Value::Number(right)) => left == right,
+            (Value::Boolean(left), Value::Boolean(right)) => left == right,
+            (Value::None, Value::None) => true,
+            (Value::Native(left), Value::Native(right)) => Rc::ptr_eq(left, right),
+            (Value::List(left), Value::List(right)) => Rc::ptr_eq(left, right),
+            (Value::Map(left), Value::Map(right)) => Rc::ptr_eq(left, right),
+            _ => false,
+        }
+    }
+}
+
+impl core::fmt::Display for Value {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let value_str = match self {
+            Self::String(s) => s.to_string(),
+            Self::Number(n) => n.to_string(),
+            Self::Boolean(b) => b.to_string(),
+            Self::None => "nil".to_string(),
+            Self::Native(native) => format!("<native fn {}>", native.name),
+            Self::List(_) | Self::Map(_) => nested_to_string(self),
+        };
+
+        write!(f, "{}", value_str)
+    }
+}
+
+// How a value prints inside a list or a map. Strings are quoted there, or
+// `["a, b"]` would look like two items. A list or map that holds itself,
+// directly or not, prints as `[...]` or `{...}` the second time instead of
+// forever. Lists can nest deeper than the Rust stack goes, so what is left to
+// print is kept on a stack of its own; `open` are the lists and maps being
+// printed around the value on top of it.
+fn nested_to_string(value: &Value) -> String {
+    enum Part {
+        Value(Value),
+        Text(&'static str),
+        Close(&'static str, *const ()),
+    }
+
+    let mut printed = String::new();
+    let mut open = HashSet::new();
+    let mut parts = vec![Part::Value(value.clone())];
+    while let Some(part) = parts.pop() {
+        let value = match part {
+            Part::Value(value) => value,
+            Part::Text(text) => {
+                printed.push_str(text);
+                continue;
+            }
+            Part::Close(text, pointer) => {
+                printed.push_str(text);
+                open.remove(&pointer);
+                continue;
+            }
+        };
+
+        let pointer = match &value {
+            Value::String(string) => {
+                printed.push_str(&format!("{string:?}"));
+                continue;
+            }
+            Value::List(list) => Rc::as_ptr(list) as *const (),
+            Value::Map(map) => Rc::as_ptr(map) as *const (),
+            value => {
+                printed.push_str(&value.to_string());
+                continue;
+            }
+        };
+        if !open.insert(pointer) {
+            printed.push_str(if matches!(value, Value::List(_)) { "[...]" } else { "{...}" });
+            continue;
+        }
+
+        // Pushed in reverse, so they come off the stack in order.
+        match &value {
+            Value::List(list) => {
+                printed.push('[');
+                parts.push(Part::Close("]", pointer));
+                for (index, item) in list.borrow().iter().enumerate().rev() {
+                    parts.push(Part::Value(item.clone()));
+                    if index > 0 {
+                        parts.push(Part::Text(", "));
+                    }
+                }
+            }
+            Value::Map(map) => {
+                printed.push('{');
+                parts.push(Part::Close("}", pointer));
+                for (index, (key, value)) in map.borrow().iter().enumerate().rev() {
+                    parts.push(Part::Value(value.clone()));
+                    parts.push(Part::Text(": "));
+                    parts.push(Part::Value(key.value().clone()));
+                    if index > 0 {
+                        parts.push(Part::Text(", "));
+                    }
+                }
+            }
+            _ => unreachable!("only lists and maps hold other values"),
+        }
+    }
+    printed
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub token_type: TokenType,
+    pub lexeme: String,
+    pub literal: Value,
+    pub line: usize,
+    pub start: usize,
+}
+
+impl Token {
+    pub fn new(token_type: TokenType, lexeme: String, literal: Value, line: usize, start: usize) -> Self {
+        Self {
+            token_type,
+            lexeme,
+            literal,
+            line,
+            start,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        Span {
+            start: self.start,
+            end: self.start + self.lexeme.len(),
+            line: self.line,
+        }
+    }
+}
+
+impl core::fmt::Display for Token {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Token Type: {}\nLexeme: {}\nLiteral: {}\nLine: {}\n",
+            self.token_type, self.lexeme, self.literal, self.line
+        )
+    }
+}
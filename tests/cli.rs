@@ -0,0 +1,98 @@
+// The `rlox` binary itself: what its subcommands print and how they exit.
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+// Runs `rlox` with `args`, giving it `stdin`.
+fn rlox(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rlox"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(args: &[&str], stdin: &str) -> String {
+    let output = rlox(args, stdin);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn parse_prints_the_tree_as_written() {
+    assert_eq!(
+        stdout(&["parse", "-"], "for (var i = 0; i < 2; i = i + 1) print i;\nbreak;\n"),
+        "(for (var i 0) (< i 2) (= i (+ i 1)) (print i))\nbreak\n"
+    );
+    assert_eq!(stdout(&["parse", "-"], "for (;;) {}"), "(for nil nil nil (block))\n");
+    assert_eq!(
+        stdout(&["parse", "-"], "print {\"a\": 1, \"b\": -x};\nprint ({});"),
+        "(print (map (\"a\" 1) (\"b\" (- x))))\n(print (group (map)))\n"
+    );
+}
+
+#[test]
+fn parse_as_tree() {
+    assert_eq!(
+        stdout(&["parse", "--format=tree", "-"], "if (!ok) print [1];"),
+        "If\n  condition: Unary !\n    right: Variable ok\n  then_branch: Print\n    expression: List\n      elements: Literal 1\n"
+    );
+}
+
+#[test]
+fn parse_as_json_with_spans() {
+    assert_eq!(
+        stdout(&["parse", "--format=json", "-"], "var s = \"ñ\";\nprint -s;"),
+        concat!(
+            "[{\"type\": \"Var\", \"id\": 0, \"span\": {\"line\": 1, \"start\": 0, \"end\": 13}, \"name\": \"s\", ",
+            "\"initializer\": {\"type\": \"Literal\", \"id\": 0, \"span\": {\"line\": 1, \"start\": 8, \"end\": 12}, \"value\": \"ñ\"}}, ",
+            "{\"type\": \"Print\", \"id\": 1, \"span\": {\"line\": 2, \"start\": 14, \"end\": 23}, ",
+            "\"expression\": {\"type\": \"Unary\", \"id\": 2, \"span\": {\"line\": 2, \"start\": 20, \"end\": 22}, \"operator\": \"-\", ",
+            "\"right\": {\"type\": \"Variable\", \"id\": 1, \"span\": {\"line\": 2, \"start\": 21, \"end\": 22}, \"name\": \"s\"}}}]\n"
+        )
+    );
+}
+
+#[test]
+fn parse_as_dot() {
+    assert_eq!(
+        stdout(&["parse", "--format=dot", "-"], "print a + \"\\\";"),
+        concat!(
+            "digraph ast {\n",
+            "  node [shape=box];\n",
+            "  s0 [label=\"Print\"];\n",
+            "  s0 -> e2 [label=\"expression\"];\n",
+            "  e2 [label=\"Binary +\"];\n",
+            "  e2 -> e0 [label=\"left\"];\n",
+            "  e0 [label=\"Variable a\"];\n",
+            "  e2 -> e1 [label=\"right\"];\n",
+            "  e1 [label=\"Literal \\\"\\\\\\\\\\\"\"];\n",
+            "}\n"
+        )
+    );
+}
+
+#[test]
+fn wrong_usage_and_static_errors_have_their_exit_codes() {
+    let output = rlox(&["--frobnicate"], "");
+    assert_eq!(output.status.code(), Some(64));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "Unknown option '--frobnicate', use --help to see the available ones\n"
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&rlox(&["parse", "--format=xml", "-"], "").stderr),
+        "Unknown format 'xml', expected sexpr, tree, json or dot\n"
+    );
+
+    let output = rlox(&["check", "-"], "#\nprint \"open;");
+    assert_eq!(output.status.code(), Some(65));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "[1] | Error: Unexpected character.\n[2] | Error: Unterminated string.\n"
+    );
+}
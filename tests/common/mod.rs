@@ -0,0 +1,25 @@
+// Helpers shared by the integration tests. Each test file is a crate of its
+// own and uses only some of them.
+#![allow(dead_code)]
+
+use rlox::{Error, Interpreter, Value};
+
+// The value of `source` run in a fresh interpreter.
+pub fn eval(source: &str) -> Value {
+    Interpreter::new().eval(source).unwrap()
+}
+
+// The runtime error `source` stops with, as the CLI would print it.
+pub fn error(source: &str) -> String {
+    match Interpreter::new().eval(source) {
+        Err(Error::Runtime(error)) => error.to_string(),
+        other => panic!("expected a runtime error, got {other:?}"),
+    }
+}
+
+// What `source` prints, one line per `print`.
+pub fn output(source: &str) -> String {
+    let mut interpreter = Interpreter::with_output(Vec::new());
+    interpreter.run(source).unwrap();
+    String::from_utf8(interpreter.into_output()).unwrap()
+}
@@ -0,0 +1,163 @@
+// The library as another crate sees it.
+
+use std::io::BufRead;
+use std::time::Duration;
+
+use rlox::ast::Span;
+use rlox::{argument, expr, stmt, Error, ErrorKind, Interpreter, Limits, RuntimeError, Value, MAX_HEIGHT, MAX_NESTING};
+
+fn runtime_error(result: Result<(), Error>) -> RuntimeError {
+    match result {
+        Err(Error::Runtime(error)) => error,
+        other => panic!("expected a runtime error, got {other:?}"),
+    }
+}
+
+#[test]
+fn eval_gives_back_the_value_of_an_expression() {
+    let mut interpreter = Interpreter::new();
+    assert_eq!(interpreter.eval("1 + 2 * 3").unwrap(), Value::Number(7.0));
+    assert_eq!(interpreter.eval("\"a\" + \"b\"").unwrap(), Value::String("ab".to_string()));
+}
+
+#[test]
+fn definitions_stay_between_calls() {
+    let mut interpreter = Interpreter::new();
+    let program = "var total = 0; for (var i = 1; i <= 4; i = i + 1) total = total + i;";
+    assert_eq!(interpreter.eval(program).unwrap(), Value::None);
+    assert_eq!(interpreter.eval("total").unwrap(), Value::Number(10.0));
+}
+
+#[test]
+fn errors_tell_syntax_from_runtime() {
+    let mut interpreter = Interpreter::new();
+    match interpreter.eval("print (;") {
+        Err(Error::Syntax(errors)) => assert_eq!(errors[0].message, "Expect expression."),
+        other => panic!("expected a syntax error, got {other:?}"),
+    }
+    match interpreter.eval("-\"a\"") {
+        Err(Error::Runtime(error)) => assert_eq!(error.message, "Operand must be a number."),
+        other => panic!("expected a runtime error, got {other:?}"),
+    }
+}
+
+#[test]
+fn natives_are_called_with_converted_arguments() {
+    let mut interpreter = Interpreter::new();
+    interpreter.define_native("greet", 2, |arguments| {
+        let name = argument::<String>(arguments, 0)?;
+        let times = argument::<Option<f64>>(arguments, 1)?.unwrap_or(1.0);
+        Ok(Value::from(format!("hello {name}").repeat(times as usize)))
+    });
+    assert_eq!(interpreter.eval("greet(\"ana\", nil)").unwrap(), Value::from("hello ana"));
+    assert_eq!(interpreter.eval("greet(\"bo\", 2)").unwrap(), Value::from("hello bohello bo"));
+}
+
+#[test]
+fn natives_check_their_arguments() {
+    let mut interpreter = Interpreter::new();
+    interpreter.define_native("half", 1, |arguments| Ok(Value::from(argument::<f64>(arguments, 0)? / 2.0)));
+    interpreter.define_native("fail", 0, |_| Err(RuntimeError::native("Nope.")));
+
+    let message = |result: Result<Value, Error>| match result {
+        Err(Error::Runtime(error)) => error.message,
+        other => panic!("expected a runtime error, got {other:?}"),
+    };
+    assert_eq!(message(interpreter.eval("half(1, 2)")), "Expected 1 arguments but got 2.");
+    assert_eq!(message(interpreter.eval("half(\"a\")")), "Argument 1 must be a number.");
+    assert_eq!(message(interpreter.eval("fail()")), "Nope.");
+    assert_eq!(message(interpreter.eval("\"half\"(1)")), "Can only call functions and classes.");
+}
+
+#[test]
+fn output_and_input_can_be_redirected() {
+    let mut interpreter = Interpreter::with_output(Vec::new());
+    interpreter.set_input("first\nsecond\n".as_bytes());
+    let input = interpreter.input();
+    interpreter.define_native("readLine", 0, move |_| {
+        let mut line = String::new();
+        input.borrow_mut().read_line(&mut line).map_err(|error| RuntimeError::native(&error.to_string()))?;
+        Ok(Value::from(line.trim_end()))
+    });
+
+    interpreter.run("print readLine(); print 1 + 1; print readLine();").unwrap();
+    assert_eq!(String::from_utf8(interpreter.into_output()).unwrap(), "first\n2\nsecond\n");
+}
+
+#[test]
+fn limits_stop_runaway_scripts() {
+    let mut interpreter = Interpreter::new();
+    interpreter.set_limits(Limits {
+        max_steps: Some(1000),
+        ..Limits::default()
+    });
+    assert_eq!(runtime_error(interpreter.run("while (true) {}")).kind, ErrorKind::StepLimit);
+    // The count starts over with every run.
+    interpreter.run("var a = 1 + 2;").unwrap();
+
+    interpreter.set_limits(Limits {
+        timeout: Some(Duration::from_millis(20)),
+        ..Limits::default()
+    });
+    assert_eq!(runtime_error(interpreter.run("while (true) {}")).kind, ErrorKind::TimeLimit);
+
+    interpreter.set_limits(Limits {
+        max_heap: Some(1 << 20),
+        ..Limits::default()
+    });
+    let error = runtime_error(interpreter.run("var s = \"x\"; while (true) s = s + s;"));
+    assert_eq!((error.kind, error.message.as_str()), (ErrorKind::HeapLimit, "Heap limit exceeded."));
+}
+
+#[test]
+fn deep_nesting_is_a_syntax_error() {
+    let mut interpreter = Interpreter::new();
+    let depth = MAX_NESTING - 1;
+    let nested = format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+    assert_eq!(interpreter.eval(&nested).unwrap(), Value::Number(1.0));
+    let chain = format!("0{}", " + 1".repeat(MAX_HEIGHT / 2));
+    assert_eq!(interpreter.eval(&chain).unwrap(), Value::Number((MAX_HEIGHT / 2) as f64));
+
+    let deep = [
+        format!("{}1{}", "(".repeat(20_000), ")".repeat(20_000)),
+        format!("{}1", "-".repeat(50_000)),
+        format!("1{}", " + 1".repeat(50_000)),
+        format!("{}{}", "{".repeat(20_000), "}".repeat(20_000)),
+        format!("{}{}", "[".repeat(20_000), "]".repeat(20_000)),
+    ];
+    for source in deep {
+        match interpreter.eval(&source) {
+            Err(Error::Syntax(errors)) => assert_eq!(errors[0].message, "Too deeply nested."),
+            other => panic!("expected a syntax error, got {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn trees_built_by_hand_stop_at_the_depth_limit() {
+    let mut interpreter = Interpreter::new();
+    let ast = interpreter.ast_mut();
+    let mut expr = ast.alloc_expr(expr::Literal::new(Value::Number(1.0)), Span::default());
+    for _ in 0..100_000 {
+        expr = ast.alloc_expr(expr::Grouping::new(expr), Span::default());
+    }
+    let statement = ast.alloc_stmt(stmt::Expression::new(expr), Span::default());
+
+    let error = interpreter.interpret(&[statement]).unwrap_err();
+    assert_eq!((error.kind, error.message.as_str()), (ErrorKind::StackOverflow, "Stack overflow."));
+}
+
+#[test]
+fn io_natives_only_work_when_allowed() {
+    let mut interpreter = Interpreter::new();
+    interpreter.define_io_native("touch", 0, |_| Ok(Value::Boolean(true)));
+    let error = runtime_error(interpreter.run("touch();"));
+    assert_eq!(error.kind, ErrorKind::IoDisabled);
+    assert_eq!(error.message, "I/O is disabled, can't call 'touch'.");
+
+    interpreter.set_limits(Limits {
+        allow_io: true,
+        ..Limits::default()
+    });
+    interpreter.run("touch();").unwrap();
+}
@@ -0,0 +1,117 @@
+// Lists: literals, indexing and their methods.
+
+mod common;
+
+use common::{error, eval};
+use rlox::{Error, ErrorKind, Interpreter, Limits, Value};
+
+#[test]
+fn literals_and_indexing() {
+    assert_eq!(eval("str([1, \"a\", [true, nil]])"), Value::from("[1, \"a\", [true, nil]]"));
+    assert_eq!(eval("[10, 20, 30][1]"), Value::Number(20.0));
+    assert_eq!(eval("str([])"), Value::from("[]"));
+
+    let mut interpreter = Interpreter::new();
+    interpreter.run("var xs = [[1, 2], [3]]; xs[0][1] = xs[1][0] = 4;").unwrap();
+    assert_eq!(interpreter.eval("str(xs)").unwrap(), Value::from("[[1, 4], [4]]"));
+}
+
+#[test]
+fn methods() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .run("var xs = [1, 2]; xs.push(3); xs.insert(0, 0); xs.insert(4, 4); var last = xs.pop(); var second = xs.remove(1);")
+        .unwrap();
+    assert_eq!(interpreter.eval("str(xs)").unwrap(), Value::from("[0, 2, 3]"));
+    assert_eq!(interpreter.eval("last + second").unwrap(), Value::Number(5.0));
+    assert_eq!(interpreter.eval("xs.len() == len(xs)").unwrap(), Value::Boolean(true));
+}
+
+#[test]
+fn growing_a_list_counts_against_the_heap_limit() {
+    let mut interpreter = Interpreter::new();
+    // The step limit only ends the loop if the heap limit never does.
+    interpreter.set_limits(Limits {
+        max_heap: Some(1 << 16),
+        max_steps: Some(1_000_000),
+        ..Limits::default()
+    });
+    for source in ["var xs = []; while (true) xs.push(1);", "var xs = []; while (true) xs.insert(0, \"abc\");"] {
+        match interpreter.run(source) {
+            Err(Error::Runtime(error)) => assert_eq!(error.kind, ErrorKind::HeapLimit, "{source}"),
+            other => panic!("expected a runtime error, got {other:?}"),
+        }
+    }
+
+    // The item is charged before it goes in, so the list is left as it was.
+    let text = "x".repeat(40_000);
+    let source = format!("var ys = []; ys.push(\"{text}\"); ys.insert(0, \"{text}\");");
+    match interpreter.run(&source) {
+        Err(Error::Runtime(error)) => assert_eq!(error.kind, ErrorKind::HeapLimit),
+        other => panic!("expected a runtime error, got {other:?}"),
+    }
+    assert_eq!(interpreter.eval("ys.len()").unwrap(), Value::Number(1.0));
+}
+
+#[test]
+fn index_assignment_counts_against_the_heap_limit() {
+    let mut interpreter = Interpreter::new();
+    interpreter.set_limits(Limits {
+        max_heap: Some(1 << 16),
+        max_steps: Some(1_000_000),
+        ..Limits::default()
+    });
+    match interpreter.run("var xs = [nil]; var s = \"0123456789\"; while (true) xs[0] = s;") {
+        Err(Error::Runtime(error)) => assert_eq!(error.kind, ErrorKind::HeapLimit),
+        other => panic!("expected a runtime error, got {other:?}"),
+    }
+}
+
+#[test]
+fn sharing_a_list_only_costs_the_slot() {
+    let mut interpreter = Interpreter::new();
+    interpreter.set_limits(Limits {
+        max_heap: Some(200_000),
+        ..Limits::default()
+    });
+    interpreter
+        .run("var big = []; for (i in range(0, 1000, 1)) big.push(i); for (i in range(0, 100, 1)) { var x = [big]; }")
+        .unwrap();
+}
+
+#[test]
+fn lists_can_nest_deeper_than_the_rust_stack() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .run("var b = []; var a = b; for (i in range(0, 100000, 1)) { var n = []; a.push(n); a = n; } var c = [b];")
+        .unwrap();
+    assert_eq!(interpreter.eval("len(str(c))").unwrap(), Value::Number(200_004.0));
+    interpreter.run("var d = {}; for (i in range(0, 100000, 1)) d = {\"k\": [d]};").unwrap();
+    assert_eq!(interpreter.eval("len(str(d))").unwrap(), Value::Number(900_002.0));
+    // Dropping them goes as deep as printing them.
+    interpreter.run("b = nil; c = nil; d = nil;").unwrap();
+}
+
+#[test]
+fn lists_are_shared_not_copied() {
+    let mut interpreter = Interpreter::new();
+    interpreter.run("var a = [1]; var b = a; b.push(2); b[0] = 0;").unwrap();
+    assert_eq!(interpreter.eval("str(a)").unwrap(), Value::from("[0, 2]"));
+    assert_eq!(interpreter.eval("a == b").unwrap(), Value::Boolean(true));
+    assert_eq!(interpreter.eval("a == [0, 2]").unwrap(), Value::Boolean(false));
+
+    interpreter.run("a.push(a);").unwrap();
+    assert_eq!(interpreter.eval("str(a)").unwrap(), Value::from("[0, 2, [...]]"));
+}
+
+#[test]
+fn bad_indices_are_runtime_errors() {
+    assert_eq!(error("[1, 2][-1];"), "[1] | Error at '[': Index can't be negative, got -1.");
+    assert_eq!(error("[1, 2][2];"), "[1] | Error at '[': Index 2 is out of bounds for a list of length 2.");
+    assert_eq!(error("[1, 2][0.5] = 1;"), "[1] | Error at '[': Index must be a whole number, got 0.5.");
+    assert_eq!(error("[1, 2][\"0\"];"), "[1] | Error at '[': Index must be a number.");
+    assert_eq!(error("\"ab\"[0];"), "[1] | Error at '[': Only lists and maps can be indexed.");
+    assert_eq!(error("[].remove(0);"), "[1] | Error in remove(): Index 0 is out of bounds for a list of length 0.");
+    assert_eq!(error("[].pop();"), "[1] | Error in pop(): Can't pop from an empty list.");
+    assert_eq!(error("[].sort();"), "[1] | Error at 'sort': Undefined method 'sort' for a list.");
+}
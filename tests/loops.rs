@@ -0,0 +1,116 @@
+// Loops over collections with `for (x in ...)`, and leaving loops early with
+// `break` and `continue`.
+
+mod common;
+
+use common::{error, output};
+use rlox::ast::{Ast, StmtId};
+use rlox::resolver::Resolver;
+use rlox::{Error, Interpreter, Parser, Scanner, Value};
+
+// The tree as the parser builds it, with `for` not yet lowered to `while`.
+fn parse_raw(source: &str, ast: &mut Ast) -> Vec<StmtId> {
+    let tokens = Scanner::new(source.to_string()).scan_tokens().to_vec();
+    Parser::new(tokens, ast).parse().unwrap()
+}
+
+#[test]
+fn for_in_goes_through_lists_map_keys_and_characters() {
+    assert_eq!(output("for (x in [1, \"a\"]) print x;"), "1\na\n");
+    assert_eq!(output("for (key in {\"b\": 1, \"a\": 2}) print key;"), "b\na\n");
+    assert_eq!(output("for (char in \"ñu\") print char;"), "ñ\nu\n");
+    assert_eq!(output("for (x in []) print x;"), "");
+}
+
+#[test]
+fn ranges_count_up_or_down() {
+    assert_eq!(output("for (i in range(0, 3, 1)) print i;"), "0\n1\n2\n");
+    assert_eq!(output("for (i in range(1, 0, -0.5)) print i;"), "1\n0.5\n");
+    assert_eq!(output("for (i in range(0, 0, 1)) print i;"), "");
+    assert_eq!(error("range(0, 1, 0);"), "[1] | Error in range(): Argument 3 must be a number other than 0.");
+}
+
+#[test]
+fn functions_are_called_until_they_give_nil() {
+    let mut interpreter = Interpreter::with_output(Vec::new());
+    interpreter.set_input("one\ntwo\n".as_bytes());
+    interpreter.run("for (line in input) print \"> \" + line;").unwrap();
+    assert_eq!(interpreter.output(), b"> one\n> two\n");
+}
+
+#[test]
+fn the_loop_variable_is_new_each_time_and_stays_inside() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .run("var x = \"outer\"; var xs = [1]; for (x in xs) if (x < 3) xs.push(x + 1);")
+        .unwrap();
+    assert_eq!(interpreter.eval("x").unwrap(), Value::from("outer"));
+    assert_eq!(interpreter.eval("str(xs)").unwrap(), Value::from("[1, 2, 3]"));
+}
+
+#[test]
+fn only_some_values_can_be_iterated() {
+    assert_eq!(
+        error("for (x in 1) {}"),
+        "[1] | Error at 'x': Can only iterate over lists, maps, strings and functions."
+    );
+    assert_eq!(
+        error("for (x in sqrt) {}"),
+        "[1] | Error at 'x': Can only iterate over functions that take no arguments."
+    );
+}
+
+#[test]
+fn break_leaves_the_innermost_loop() {
+    assert_eq!(output("var i = 0; while (true) { if (i == 2) break; print i; i = i + 1; }"), "0\n1\n");
+    assert_eq!(
+        output("for (var i = 0; i < 2; i = i + 1) for (x in [\"a\", \"b\"]) { if (x == \"b\") break; print x; }"),
+        "a\na\n"
+    );
+}
+
+#[test]
+fn continue_still_runs_the_increment_of_a_for() {
+    assert_eq!(
+        output("for (var i = 0; i < 5; i = i + 1) { if (i == 2) continue; if (i == 4) break; print i; }"),
+        "0\n1\n3\n"
+    );
+    assert_eq!(output("for (x in range(0, 4, 1)) { if (x < 2) continue; print x; }"), "2\n3\n");
+}
+
+#[test]
+fn break_and_continue_outside_a_loop_are_static_errors() {
+    let mut interpreter = Interpreter::with_output(Vec::new());
+    match interpreter.run("print 1; if (true) break; { continue; }") {
+        Err(Error::Syntax(errors)) => {
+            let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+            assert_eq!(
+                messages,
+                [
+                    "[1] | Error at 'break': Can't use 'break' outside of a loop.",
+                    "[1] | Error at 'continue': Can't use 'continue' outside of a loop."
+                ]
+            );
+        }
+        other => panic!("expected syntax errors, got {other:?}"),
+    }
+    assert_eq!(interpreter.output(), b"");
+}
+
+#[test]
+fn for_loops_that_skipped_desugar_are_still_checked() {
+    let mut ast = Ast::new();
+    let statements = parse_raw("for (var i = 0; i < 3; i = i + 1) { if (i == 1) continue; break; } break;", &mut ast);
+    let errors = Resolver::new(&ast).run(&statements).unwrap_err();
+    let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+    assert_eq!(messages, ["[1] | Error at 'break': Can't use 'break' outside of a loop."]);
+
+    let mut interpreter = Interpreter::new();
+    let statements = parse_raw("print 1;\nfor (;;) break;", interpreter.ast_mut());
+    let error = interpreter.interpret(&statements).unwrap_err();
+    assert_eq!(error.to_string(), "[2] | Error: For loops must go through Desugar before running.");
+
+    let statements = parse_raw("print 1;\nif (true) continue;", interpreter.ast_mut());
+    let error = interpreter.interpret(&statements).unwrap_err();
+    assert_eq!(error.to_string(), "[2] | Error: Can't use 'continue' outside of a loop.");
+}
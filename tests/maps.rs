@@ -0,0 +1,82 @@
+// Maps: literals, lookups by key and their methods.
+
+mod common;
+
+use common::{error, eval};
+use rlox::{Error, ErrorKind, Interpreter, Key, Limits, Value};
+
+#[test]
+fn literals_and_lookups() {
+    assert_eq!(
+        eval("str({\"a\": 1, 2: [true], nil: {}})"),
+        Value::from("{\"a\": 1, 2: [true], nil: {}}")
+    );
+    assert_eq!(eval("{\"a\": 1, \"b\": 2}[\"b\"]"), Value::Number(2.0));
+    assert_eq!(eval("{0: \"zero\"}[-0]"), Value::from("zero"));
+
+    let mut interpreter = Interpreter::new();
+    interpreter.run("var m = {\"a\": 1}; m[\"b\"] = 2; m[\"a\"] = 3;").unwrap();
+    assert_eq!(interpreter.eval("str(m)").unwrap(), Value::from("{\"a\": 3, \"b\": 2}"));
+}
+
+#[test]
+fn methods_and_iterating_over_keys() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .run(
+            "var m = {\"x\": 1, \"y\": 2, \"z\": 3};
+             var removed = m.remove(\"y\");
+             var sum = 0;
+             var keys = m.keys();
+             var i = 0;
+             while (i < len(keys)) { sum = sum + m[keys[i]]; i = i + 1; }",
+        )
+        .unwrap();
+    assert_eq!(interpreter.eval("sum + removed").unwrap(), Value::Number(6.0));
+    assert_eq!(interpreter.eval("str(m.values())").unwrap(), Value::from("[1, 3]"));
+    assert_eq!(interpreter.eval("m.has(\"x\") and !m.has(\"y\")").unwrap(), Value::Boolean(true));
+    assert_eq!(interpreter.eval("m.len() + len(m)").unwrap(), Value::Number(4.0));
+}
+
+#[test]
+fn new_entries_count_against_the_heap_limit() {
+    let mut interpreter = Interpreter::new();
+    // The step limit only ends the loop if the heap limit never does.
+    interpreter.set_limits(Limits {
+        max_heap: Some(1 << 16),
+        max_steps: Some(1_000_000),
+        ..Limits::default()
+    });
+    match interpreter.run("var m = {}; var i = 0; while (true) { m[i] = i; i = i + 1; }") {
+        Err(Error::Runtime(error)) => assert_eq!(error.kind, ErrorKind::HeapLimit),
+        other => panic!("expected a runtime error, got {other:?}"),
+    }
+}
+
+#[test]
+fn maps_are_shared_and_built_from_rust() {
+    let mut interpreter = Interpreter::new();
+    interpreter.run("var a = {}; var b = a; b[1] = a;").unwrap();
+    assert_eq!(interpreter.eval("str(a)").unwrap(), Value::from("{1: {...}}"));
+    assert_eq!(interpreter.eval("a == b and a != {}").unwrap(), Value::Boolean(true));
+
+    let key = Key::new(Value::from("k")).unwrap();
+    interpreter.define_native("config", 0, move |_| Ok(Value::map([(key.clone(), Value::Number(1.0))])));
+    assert_eq!(interpreter.eval("config()[\"k\"]").unwrap(), Value::Number(1.0));
+    assert!(Key::new(Value::list(vec![])).is_none());
+}
+
+#[test]
+fn bad_keys_are_runtime_errors() {
+    assert_eq!(error("({\"a\": 1})[\"b\"];"), "[1] | Error at '[': Key \"b\" not found.");
+    assert_eq!(
+        error("var m = {[]: 1};"),
+        "[1] | Error at '{': Only strings, numbers, booleans and nil can be map keys, got a list."
+    );
+    assert_eq!(
+        error("var m = {}; m[m] = 1;"),
+        "[1] | Error at '[': Only strings, numbers, booleans and nil can be map keys, got a map."
+    );
+    assert_eq!(error("({}).remove(1);"), "[1] | Error in remove(): Key 1 not found.");
+    assert_eq!(error("({}).push(1);"), "[1] | Error at 'push': Undefined method 'push' for a map.");
+}
@@ -0,0 +1,55 @@
+// The natives every interpreter comes with.
+
+mod common;
+
+use common::{error, eval};
+use rlox::{Interpreter, Value};
+
+#[test]
+fn strings() {
+    assert_eq!(eval("len(\"ñandú\")"), Value::Number(5.0));
+    assert_eq!(eval("upper(\"ñandú\") + lower(\"ABC\")"), Value::from("ÑANDÚabc"));
+    assert_eq!(eval("substr(\"hello\", 1, 3)"), Value::from("ell"));
+    assert_eq!(eval("substr(\"hi\", 1, 10)"), Value::from("i"));
+    assert_eq!(eval("str(split(\"a,b,,c\", \",\"))"), Value::from("[\"a\", \"b\", \"\", \"c\"]"));
+    assert_eq!(eval("len(split(\"abc\", \"\"))"), Value::Number(3.0));
+}
+
+#[test]
+fn conversions_and_types() {
+    assert_eq!(eval("str(1.5) + str(nil)"), Value::from("1.5nil"));
+    assert_eq!(eval("num(\" 42 \") + num(\"0.5\")"), Value::Number(42.5));
+    assert_eq!(eval("num(\"4x\")"), Value::None);
+    assert_eq!(eval("num(\"inf\")"), Value::None);
+    assert_eq!(
+        eval("type(1) + type(\"\") + type(true) + type(nil) + type(clock)"),
+        Value::from("numberstringbooleannilfunction")
+    );
+}
+
+#[test]
+fn math() {
+    assert_eq!(eval("sqrt(16) + floor(-2.5) + abs(-1)"), Value::Number(2.0));
+    assert_eq!(eval("min(3, 4) * max(3, 4)"), Value::Number(12.0));
+    let mut interpreter = Interpreter::new();
+    interpreter.run("seed(7); var a = random(); seed(7); var b = random();").unwrap();
+    assert_eq!(interpreter.eval("a == b and a >= 0 and a < 1").unwrap(), Value::Boolean(true));
+}
+
+#[test]
+fn bad_arguments_are_reported_the_same_way() {
+    assert_eq!(error("sqrt(\"4\");"), "[1] | Error in sqrt(): Argument 1 must be a number.");
+    assert_eq!(error("len(1);"), "[1] | Error in len(): Argument 1 must be a string, a list or a map.");
+    assert_eq!(
+        error("substr(\"a\", 0, -1);"),
+        "[1] | Error in substr(): Argument 3 must be a whole number, not negative."
+    );
+}
+
+#[test]
+fn input_reads_lines_until_there_are_none() {
+    let mut interpreter = Interpreter::with_output(Vec::new());
+    interpreter.set_input("one\r\ntwo".as_bytes());
+    interpreter.run("print input(); print input(); print input();").unwrap();
+    assert_eq!(String::from_utf8(interpreter.into_output()).unwrap(), "one\ntwo\nnil\n");
+}